@@ -180,9 +180,13 @@ impl Btf<'static> {
             )
         })?;
 
-        let ptr = create_bpf_entity_checked(|| unsafe {
-            libbpf_sys::btf__load_from_kernel_by_id(info.btf_id)
-        })?;
+        Self::from_id(info.btf_id)
+    }
+
+    /// Load the btf information with the given BTF object id from the kernel.
+    pub(crate) fn from_id(id: u32) -> Result<Self> {
+        let ptr =
+            create_bpf_entity_checked(|| unsafe { libbpf_sys::btf__load_from_kernel_by_id(id) })?;
 
         Ok(Self {
             ptr,
@@ -193,6 +197,21 @@ impl Btf<'static> {
 }
 
 impl<'btf> Btf<'btf> {
+    /// Load the split btf information with the given BTF object id from the
+    /// kernel, on top of `base`, e.g., that of a kernel module on top of
+    /// vmlinux's.
+    pub(crate) fn from_id_split(id: u32, base: &'btf Btf<'_>) -> Result<Self> {
+        let ptr = create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::btf__load_from_kernel_by_id_split(id, base.ptr.as_ptr())
+        })?;
+
+        Ok(Self {
+            ptr,
+            drop_policy: DropPolicy::SelfPtrOnly,
+            _marker: PhantomData,
+        })
+    }
+
     /// Create a new `Btf` instance from the given [`libbpf_sys::bpf_object`].
     pub fn from_bpf_object(obj: &'btf libbpf_sys::bpf_object) -> Result<Option<Self>> {
         Self::from_bpf_object_raw(obj)
@@ -256,7 +275,7 @@ impl<'btf> Btf<'btf> {
     /// Gets a string at a given offset.
     ///
     /// Returns [`None`] when the offset is out of bounds or if the name is empty.
-    fn name_at(&self, offset: u32) -> Option<&CStr> {
+    pub(crate) fn name_at(&self, offset: u32) -> Option<&CStr> {
         let name = unsafe {
             // SAFETY:
             // Assuming that btf is a valid pointer, this is always okay to call.
@@ -0,0 +1,903 @@
+//! A minimal disassembler for BPF instructions, producing output in the
+//! style of `bpftool prog dump xlated`, as well as a decoder for programmatic
+//! analysis of instructions.
+
+use std::fmt::Write as _;
+use std::iter;
+
+use libbpf_sys::bpf_insn;
+
+/// Names of BPF helpers (without the `bpf_` prefix), indexed by their
+/// `enum bpf_func_id` value.
+static HELPER_NAMES: [&str; libbpf_sys::__BPF_FUNC_MAX_ID as usize] = [
+    "unspec",
+    "map_lookup_elem",
+    "map_update_elem",
+    "map_delete_elem",
+    "probe_read",
+    "ktime_get_ns",
+    "trace_printk",
+    "get_prandom_u32",
+    "get_smp_processor_id",
+    "skb_store_bytes",
+    "l3_csum_replace",
+    "l4_csum_replace",
+    "tail_call",
+    "clone_redirect",
+    "get_current_pid_tgid",
+    "get_current_uid_gid",
+    "get_current_comm",
+    "get_cgroup_classid",
+    "skb_vlan_push",
+    "skb_vlan_pop",
+    "skb_get_tunnel_key",
+    "skb_set_tunnel_key",
+    "perf_event_read",
+    "redirect",
+    "get_route_realm",
+    "perf_event_output",
+    "skb_load_bytes",
+    "get_stackid",
+    "csum_diff",
+    "skb_get_tunnel_opt",
+    "skb_set_tunnel_opt",
+    "skb_change_proto",
+    "skb_change_type",
+    "skb_under_cgroup",
+    "get_hash_recalc",
+    "get_current_task",
+    "probe_write_user",
+    "current_task_under_cgroup",
+    "skb_change_tail",
+    "skb_pull_data",
+    "csum_update",
+    "set_hash_invalid",
+    "get_numa_node_id",
+    "skb_change_head",
+    "xdp_adjust_head",
+    "probe_read_str",
+    "get_socket_cookie",
+    "get_socket_uid",
+    "set_hash",
+    "setsockopt",
+    "skb_adjust_room",
+    "redirect_map",
+    "sk_redirect_map",
+    "sock_map_update",
+    "xdp_adjust_meta",
+    "perf_event_read_value",
+    "perf_prog_read_value",
+    "getsockopt",
+    "override_return",
+    "sock_ops_cb_flags_set",
+    "msg_redirect_map",
+    "msg_apply_bytes",
+    "msg_cork_bytes",
+    "msg_pull_data",
+    "bind",
+    "xdp_adjust_tail",
+    "skb_get_xfrm_state",
+    "get_stack",
+    "skb_load_bytes_relative",
+    "fib_lookup",
+    "sock_hash_update",
+    "msg_redirect_hash",
+    "sk_redirect_hash",
+    "lwt_push_encap",
+    "lwt_seg6_store_bytes",
+    "lwt_seg6_adjust_srh",
+    "lwt_seg6_action",
+    "rc_repeat",
+    "rc_keydown",
+    "skb_cgroup_id",
+    "get_current_cgroup_id",
+    "get_local_storage",
+    "sk_select_reuseport",
+    "skb_ancestor_cgroup_id",
+    "sk_lookup_tcp",
+    "sk_lookup_udp",
+    "sk_release",
+    "map_push_elem",
+    "map_pop_elem",
+    "map_peek_elem",
+    "msg_push_data",
+    "msg_pop_data",
+    "rc_pointer_rel",
+    "spin_lock",
+    "spin_unlock",
+    "sk_fullsock",
+    "tcp_sock",
+    "skb_ecn_set_ce",
+    "get_listener_sock",
+    "skc_lookup_tcp",
+    "tcp_check_syncookie",
+    "sysctl_get_name",
+    "sysctl_get_current_value",
+    "sysctl_get_new_value",
+    "sysctl_set_new_value",
+    "strtol",
+    "strtoul",
+    "sk_storage_get",
+    "sk_storage_delete",
+    "send_signal",
+    "tcp_gen_syncookie",
+    "skb_output",
+    "probe_read_user",
+    "probe_read_kernel",
+    "probe_read_user_str",
+    "probe_read_kernel_str",
+    "tcp_send_ack",
+    "send_signal_thread",
+    "jiffies64",
+    "read_branch_records",
+    "get_ns_current_pid_tgid",
+    "xdp_output",
+    "get_netns_cookie",
+    "get_current_ancestor_cgroup_id",
+    "sk_assign",
+    "ktime_get_boot_ns",
+    "seq_printf",
+    "seq_write",
+    "sk_cgroup_id",
+    "sk_ancestor_cgroup_id",
+    "ringbuf_output",
+    "ringbuf_reserve",
+    "ringbuf_submit",
+    "ringbuf_discard",
+    "ringbuf_query",
+    "csum_level",
+    "skc_to_tcp6_sock",
+    "skc_to_tcp_sock",
+    "skc_to_tcp_timewait_sock",
+    "skc_to_tcp_request_sock",
+    "skc_to_udp6_sock",
+    "get_task_stack",
+    "load_hdr_opt",
+    "store_hdr_opt",
+    "reserve_hdr_opt",
+    "inode_storage_get",
+    "inode_storage_delete",
+    "d_path",
+    "copy_from_user",
+    "snprintf_btf",
+    "seq_printf_btf",
+    "skb_cgroup_classid",
+    "redirect_neigh",
+    "per_cpu_ptr",
+    "this_cpu_ptr",
+    "redirect_peer",
+    "task_storage_get",
+    "task_storage_delete",
+    "get_current_task_btf",
+    "bprm_opts_set",
+    "ktime_get_coarse_ns",
+    "ima_inode_hash",
+    "sock_from_file",
+    "check_mtu",
+    "for_each_map_elem",
+    "snprintf",
+    "sys_bpf",
+    "btf_find_by_name_kind",
+    "sys_close",
+    "timer_init",
+    "timer_set_callback",
+    "timer_start",
+    "timer_cancel",
+    "get_func_ip",
+    "get_attach_cookie",
+    "task_pt_regs",
+    "get_branch_snapshot",
+    "trace_vprintk",
+    "skc_to_unix_sock",
+    "kallsyms_lookup_name",
+    "find_vma",
+    "loop",
+    "strncmp",
+    "get_func_arg",
+    "get_func_ret",
+    "get_func_arg_cnt",
+    "get_retval",
+    "set_retval",
+    "xdp_get_buff_len",
+    "xdp_load_bytes",
+    "xdp_store_bytes",
+    "copy_from_user_task",
+    "skb_set_tstamp",
+    "ima_file_hash",
+    "kptr_xchg",
+    "map_lookup_percpu_elem",
+    "skc_to_mptcp_sock",
+    "dynptr_from_mem",
+    "ringbuf_reserve_dynptr",
+    "ringbuf_submit_dynptr",
+    "ringbuf_discard_dynptr",
+    "dynptr_read",
+    "dynptr_write",
+    "dynptr_data",
+    "tcp_raw_gen_syncookie_ipv4",
+    "tcp_raw_gen_syncookie_ipv6",
+    "tcp_raw_check_syncookie_ipv4",
+    "tcp_raw_check_syncookie_ipv6",
+    "ktime_get_tai_ns",
+    "user_ringbuf_drain",
+    "cgrp_storage_get",
+    "cgrp_storage_delete",
+];
+
+fn class(code: u8) -> u32 {
+    u32::from(code) & 0x07
+}
+
+fn op(code: u8) -> u32 {
+    u32::from(code) & 0xf0
+}
+
+fn source(code: u8) -> u32 {
+    u32::from(code) & 0x08
+}
+
+fn size(code: u8) -> u32 {
+    u32::from(code) & 0x18
+}
+
+fn mode(code: u8) -> u32 {
+    u32::from(code) & 0xe0
+}
+
+fn size_str(code: u8, signed: bool) -> &'static str {
+    match (size(code), signed) {
+        (libbpf_sys::BPF_B, false) => "u8",
+        (libbpf_sys::BPF_H, false) => "u16",
+        (libbpf_sys::BPF_W, false) => "u32",
+        (libbpf_sys::BPF_DW, false) => "u64",
+        (libbpf_sys::BPF_B, true) => "s8",
+        (libbpf_sys::BPF_H, true) => "s16",
+        (libbpf_sys::BPF_W, true) => "s32",
+        _ => "s64",
+    }
+}
+
+fn alu_op_str(op: u32, off: i16) -> Option<&'static str> {
+    let s = match (op, off) {
+        (libbpf_sys::BPF_ADD, _) => "+=",
+        (libbpf_sys::BPF_SUB, _) => "-=",
+        (libbpf_sys::BPF_MUL, _) => "*=",
+        (libbpf_sys::BPF_DIV, 1) => "s/=",
+        (libbpf_sys::BPF_DIV, _) => "/=",
+        (libbpf_sys::BPF_OR, _) => "|=",
+        (libbpf_sys::BPF_AND, _) => "&=",
+        (libbpf_sys::BPF_LSH, _) => "<<=",
+        (libbpf_sys::BPF_RSH, _) => ">>=",
+        (libbpf_sys::BPF_MOD, 1) => "s%=",
+        (libbpf_sys::BPF_MOD, _) => "%=",
+        (libbpf_sys::BPF_XOR, _) => "^=",
+        (libbpf_sys::BPF_MOV, _) => "=",
+        (libbpf_sys::BPF_ARSH, _) => "s>>=",
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn jmp_op_str(op: u32) -> Option<&'static str> {
+    let s = match op {
+        libbpf_sys::BPF_JEQ => "==",
+        libbpf_sys::BPF_JGT => ">",
+        libbpf_sys::BPF_JGE => ">=",
+        libbpf_sys::BPF_JSET => "&",
+        libbpf_sys::BPF_JNE => "!=",
+        libbpf_sys::BPF_JSGT => "s>",
+        libbpf_sys::BPF_JSGE => "s>=",
+        libbpf_sys::BPF_JLT => "<",
+        libbpf_sys::BPF_JLE => "<=",
+        libbpf_sys::BPF_JSLT => "s<",
+        libbpf_sys::BPF_JSLE => "s<=",
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn atomic_op_str(op: u32) -> Option<&'static str> {
+    let s = match op {
+        libbpf_sys::BPF_ADD => "add",
+        libbpf_sys::BPF_AND => "and",
+        libbpf_sys::BPF_OR => "or",
+        libbpf_sys::BPF_XOR => "xor",
+        _ => return None,
+    };
+    Some(s)
+}
+
+fn disassemble_alu(insn: &bpf_insn) -> Option<String> {
+    let code = insn.code;
+    let dst = insn.dst_reg();
+    let src = insn.src_reg();
+    let (reg, width) = if class(code) == libbpf_sys::BPF_ALU64 {
+        ('r', 64)
+    } else {
+        ('w', 32)
+    };
+
+    let text = match op(code) {
+        libbpf_sys::BPF_END => {
+            let kind = match (width, source(code)) {
+                (64, _) => "bswap",
+                (_, libbpf_sys::BPF_TO_BE) => "be",
+                _ => "le",
+            };
+            format!("r{dst} = {kind}{} r{dst}", insn.imm)
+        }
+        libbpf_sys::BPF_NEG => format!("{reg}{dst} = -{reg}{dst}"),
+        libbpf_sys::BPF_MOV if source(code) == libbpf_sys::BPF_X && insn.off != 0 => {
+            format!("{reg}{dst} = (s{}){reg}{src}", insn.off)
+        }
+        op => {
+            let op = alu_op_str(op, insn.off)?;
+            if source(code) == libbpf_sys::BPF_X {
+                format!("{reg}{dst} {op} {reg}{src}")
+            } else {
+                format!("{reg}{dst} {op} {}", insn.imm)
+            }
+        }
+    };
+    Some(text)
+}
+
+fn disassemble_jmp(insn: &bpf_insn) -> Option<String> {
+    let code = insn.code;
+    let dst = insn.dst_reg();
+    let src = insn.src_reg();
+    let is_jmp32 = class(code) == libbpf_sys::BPF_JMP32;
+    let reg = if is_jmp32 { 'w' } else { 'r' };
+
+    let text = match op(code) {
+        libbpf_sys::BPF_JA if is_jmp32 => format!("gotol pc{:+}", insn.imm),
+        libbpf_sys::BPF_JA => format!("goto pc{:+}", insn.off),
+        libbpf_sys::BPF_CALL if is_jmp32 => return None,
+        libbpf_sys::BPF_CALL => match u32::from(src) {
+            libbpf_sys::BPF_PSEUDO_CALL => format!("call pc{:+}", insn.imm),
+            libbpf_sys::BPF_PSEUDO_KFUNC_CALL => format!("call kernel-function#{}", insn.imm),
+            _ => match usize::try_from(insn.imm)
+                .ok()
+                .and_then(|id| HELPER_NAMES.get(id))
+            {
+                Some(name) => format!("call bpf_{name}#{}", insn.imm),
+                None => format!("call unknown#{}", insn.imm),
+            },
+        },
+        libbpf_sys::BPF_EXIT if is_jmp32 => return None,
+        libbpf_sys::BPF_EXIT => "exit".to_string(),
+        op => {
+            let op = jmp_op_str(op)?;
+            if source(code) == libbpf_sys::BPF_X {
+                format!("if {reg}{dst} {op} {reg}{src} goto pc{:+}", insn.off)
+            } else {
+                format!(
+                    "if {reg}{dst} {op} {:#x} goto pc{:+}",
+                    insn.imm as u32, insn.off
+                )
+            }
+        }
+    };
+    Some(text)
+}
+
+/// Disassemble a 64 bit immediate load, which spans two instruction slots.
+fn disassemble_ld_imm64(insn: &bpf_insn, next: &bpf_insn) -> String {
+    let dst = insn.dst_reg();
+    let text = match u32::from(insn.src_reg()) {
+        libbpf_sys::BPF_PSEUDO_MAP_FD => format!("r{dst} = map[fd:{}]", insn.imm),
+        libbpf_sys::BPF_PSEUDO_MAP_IDX => format!("r{dst} = map[idx:{}]", insn.imm),
+        libbpf_sys::BPF_PSEUDO_MAP_VALUE => {
+            format!("r{dst} = map[fd:{}][0]+{}", insn.imm, next.imm)
+        }
+        libbpf_sys::BPF_PSEUDO_MAP_IDX_VALUE => {
+            format!("r{dst} = map[idx:{}][0]+{}", insn.imm, next.imm)
+        }
+        libbpf_sys::BPF_PSEUDO_BTF_ID => format!("r{dst} = btf_id[{}]", insn.imm),
+        libbpf_sys::BPF_PSEUDO_FUNC => format!("r{dst} = func pc{:+}", insn.imm),
+        _ => {
+            let imm = u64::from(next.imm as u32) << 32 | u64::from(insn.imm as u32);
+            format!("r{dst} = {imm:#x} ll")
+        }
+    };
+    text
+}
+
+fn disassemble_mem(insn: &bpf_insn) -> Option<String> {
+    let code = insn.code;
+    let dst = insn.dst_reg();
+    let src = insn.src_reg();
+    let off = insn.off;
+
+    let text = match (class(code), mode(code)) {
+        (libbpf_sys::BPF_LD, libbpf_sys::BPF_ABS) => {
+            format!("r0 = *({} *)skb[{}]", size_str(code, false), insn.imm)
+        }
+        (libbpf_sys::BPF_LD, libbpf_sys::BPF_IND) => {
+            format!(
+                "r0 = *({} *)skb[r{src} + {}]",
+                size_str(code, false),
+                insn.imm
+            )
+        }
+        (libbpf_sys::BPF_LDX, libbpf_sys::BPF_MEM) => {
+            format!("r{dst} = *({} *)(r{src} {off:+})", size_str(code, false))
+        }
+        (libbpf_sys::BPF_LDX, libbpf_sys::BPF_MEMSX) => {
+            format!("r{dst} = *({} *)(r{src} {off:+})", size_str(code, true))
+        }
+        (libbpf_sys::BPF_ST, libbpf_sys::BPF_MEM) => {
+            format!(
+                "*({} *)(r{dst} {off:+}) = {}",
+                size_str(code, false),
+                insn.imm
+            )
+        }
+        (libbpf_sys::BPF_STX, libbpf_sys::BPF_MEM) => {
+            format!("*({} *)(r{dst} {off:+}) = r{src}", size_str(code, false))
+        }
+        (libbpf_sys::BPF_STX, libbpf_sys::BPF_ATOMIC) => {
+            let (reg, suffix) = if size(code) == libbpf_sys::BPF_DW {
+                ('r', "64")
+            } else {
+                ('w', "")
+            };
+            let size = size_str(code, false);
+            let imm = insn.imm as u32;
+            match imm {
+                libbpf_sys::BPF_XCHG => format!(
+                    "{reg}{src} = atomic{suffix}_xchg(({size} *)(r{dst} {off:+}), {reg}{src})"
+                ),
+                libbpf_sys::BPF_CMPXCHG => format!(
+                    "{reg}0 = atomic{suffix}_cmpxchg(({size} *)(r{dst} {off:+}), {reg}0, {reg}{src})"
+                ),
+                _ if imm & libbpf_sys::BPF_FETCH != 0 => {
+                    let op = atomic_op_str(imm & !libbpf_sys::BPF_FETCH)?;
+                    format!(
+                        "{reg}{src} = atomic{suffix}_fetch_{op}(({size} *)(r{dst} {off:+}), {reg}{src})"
+                    )
+                }
+                _ => {
+                    let op = match imm {
+                        libbpf_sys::BPF_ADD => "+=",
+                        libbpf_sys::BPF_AND => "&=",
+                        libbpf_sys::BPF_OR => "|=",
+                        libbpf_sys::BPF_XOR => "^=",
+                        _ => return None,
+                    };
+                    format!("lock *({size} *)(r{dst} {off:+}) {op} {reg}{src}")
+                }
+            }
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// The class of a BPF instruction, i.e., the lowest three bits of its
+/// opcode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InsnClass {
+    /// Non-standard load operations (`BPF_LD`).
+    Ld,
+    /// Load into register operations (`BPF_LDX`).
+    Ldx,
+    /// Store from immediate operations (`BPF_ST`).
+    St,
+    /// Store from register operations (`BPF_STX`).
+    Stx,
+    /// 32 bit arithmetic operations (`BPF_ALU`).
+    Alu,
+    /// 64 bit jump operations (`BPF_JMP`).
+    Jmp,
+    /// 32 bit jump operations (`BPF_JMP32`).
+    Jmp32,
+    /// 64 bit arithmetic operations (`BPF_ALU64`).
+    Alu64,
+}
+
+impl InsnClass {
+    fn from_code(code: u8) -> Self {
+        match class(code) {
+            libbpf_sys::BPF_LD => Self::Ld,
+            libbpf_sys::BPF_LDX => Self::Ldx,
+            libbpf_sys::BPF_ST => Self::St,
+            libbpf_sys::BPF_STX => Self::Stx,
+            libbpf_sys::BPF_ALU => Self::Alu,
+            libbpf_sys::BPF_JMP => Self::Jmp,
+            libbpf_sys::BPF_JMP32 => Self::Jmp32,
+            _ => Self::Alu64,
+        }
+    }
+}
+
+/// A decoded BPF instruction.
+///
+/// A 64 bit immediate load (`ldimm64`) occupies two instruction slots, but
+/// is decoded into a single [`DecodedInsn`], with both halves of the
+/// immediate combined in [`DecodedInsn::imm`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DecodedInsn {
+    /// The index of the (first) instruction slot the instruction occupies.
+    pub idx: usize,
+    /// The raw opcode.
+    pub opcode: u8,
+    /// The class of the instruction.
+    pub class: InsnClass,
+    /// The mnemonic of the operation, e.g., `"add"`, `"jeq32"`, `"call"`,
+    /// or `"lddw"`. Invalid instructions are reported as `"invalid"`.
+    pub mnemonic: &'static str,
+    /// The destination register.
+    pub dst_reg: u8,
+    /// The source register.
+    pub src_reg: u8,
+    /// The offset.
+    pub off: i16,
+    /// The (sign extended) immediate value.
+    pub imm: i64,
+}
+
+impl DecodedInsn {
+    /// The number of instruction slots the instruction occupies.
+    pub fn slots(&self) -> usize {
+        if self.mnemonic == "lddw" {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Check whether the instruction is a call of a BPF helper, returning the
+    /// helper's id if so.
+    pub fn helper_id(&self) -> Option<libbpf_sys::bpf_func_id> {
+        (self.mnemonic == "call" && self.src_reg == 0)
+            .then(|| libbpf_sys::bpf_func_id::try_from(self.imm).ok())
+            .flatten()
+    }
+}
+
+fn alu_mnemonic(insn: &bpf_insn) -> Option<&'static str> {
+    let code = insn.code;
+    let is_alu64 = class(code) == libbpf_sys::BPF_ALU64;
+    let signed = insn.off != 0;
+    let mnemonic = match (op(code), is_alu64) {
+        (libbpf_sys::BPF_ADD, true) => "add",
+        (libbpf_sys::BPF_ADD, false) => "add32",
+        (libbpf_sys::BPF_SUB, true) => "sub",
+        (libbpf_sys::BPF_SUB, false) => "sub32",
+        (libbpf_sys::BPF_MUL, true) => "mul",
+        (libbpf_sys::BPF_MUL, false) => "mul32",
+        (libbpf_sys::BPF_DIV, true) if signed => "sdiv",
+        (libbpf_sys::BPF_DIV, false) if signed => "sdiv32",
+        (libbpf_sys::BPF_DIV, true) => "div",
+        (libbpf_sys::BPF_DIV, false) => "div32",
+        (libbpf_sys::BPF_OR, true) => "or",
+        (libbpf_sys::BPF_OR, false) => "or32",
+        (libbpf_sys::BPF_AND, true) => "and",
+        (libbpf_sys::BPF_AND, false) => "and32",
+        (libbpf_sys::BPF_LSH, true) => "lsh",
+        (libbpf_sys::BPF_LSH, false) => "lsh32",
+        (libbpf_sys::BPF_RSH, true) => "rsh",
+        (libbpf_sys::BPF_RSH, false) => "rsh32",
+        (libbpf_sys::BPF_NEG, true) => "neg",
+        (libbpf_sys::BPF_NEG, false) => "neg32",
+        (libbpf_sys::BPF_MOD, true) if signed => "smod",
+        (libbpf_sys::BPF_MOD, false) if signed => "smod32",
+        (libbpf_sys::BPF_MOD, true) => "mod",
+        (libbpf_sys::BPF_MOD, false) => "mod32",
+        (libbpf_sys::BPF_XOR, true) => "xor",
+        (libbpf_sys::BPF_XOR, false) => "xor32",
+        (libbpf_sys::BPF_MOV, true) if signed => "movsx",
+        (libbpf_sys::BPF_MOV, false) if signed => "movsx32",
+        (libbpf_sys::BPF_MOV, true) => "mov",
+        (libbpf_sys::BPF_MOV, false) => "mov32",
+        (libbpf_sys::BPF_ARSH, true) => "arsh",
+        (libbpf_sys::BPF_ARSH, false) => "arsh32",
+        (libbpf_sys::BPF_END, true) => "bswap",
+        (libbpf_sys::BPF_END, false) if source(code) == libbpf_sys::BPF_TO_BE => "be",
+        (libbpf_sys::BPF_END, false) => "le",
+        _ => return None,
+    };
+    Some(mnemonic)
+}
+
+fn jmp_mnemonic(insn: &bpf_insn) -> Option<&'static str> {
+    let code = insn.code;
+    let is_jmp32 = class(code) == libbpf_sys::BPF_JMP32;
+    let mnemonic = match (op(code), is_jmp32) {
+        (libbpf_sys::BPF_JA, false) => "ja",
+        (libbpf_sys::BPF_JA, true) => "jal",
+        (libbpf_sys::BPF_JEQ, false) => "jeq",
+        (libbpf_sys::BPF_JEQ, true) => "jeq32",
+        (libbpf_sys::BPF_JGT, false) => "jgt",
+        (libbpf_sys::BPF_JGT, true) => "jgt32",
+        (libbpf_sys::BPF_JGE, false) => "jge",
+        (libbpf_sys::BPF_JGE, true) => "jge32",
+        (libbpf_sys::BPF_JSET, false) => "jset",
+        (libbpf_sys::BPF_JSET, true) => "jset32",
+        (libbpf_sys::BPF_JNE, false) => "jne",
+        (libbpf_sys::BPF_JNE, true) => "jne32",
+        (libbpf_sys::BPF_JSGT, false) => "jsgt",
+        (libbpf_sys::BPF_JSGT, true) => "jsgt32",
+        (libbpf_sys::BPF_JSGE, false) => "jsge",
+        (libbpf_sys::BPF_JSGE, true) => "jsge32",
+        (libbpf_sys::BPF_CALL, false) => "call",
+        (libbpf_sys::BPF_EXIT, false) => "exit",
+        (libbpf_sys::BPF_JLT, false) => "jlt",
+        (libbpf_sys::BPF_JLT, true) => "jlt32",
+        (libbpf_sys::BPF_JLE, false) => "jle",
+        (libbpf_sys::BPF_JLE, true) => "jle32",
+        (libbpf_sys::BPF_JSLT, false) => "jslt",
+        (libbpf_sys::BPF_JSLT, true) => "jslt32",
+        (libbpf_sys::BPF_JSLE, false) => "jsle",
+        (libbpf_sys::BPF_JSLE, true) => "jsle32",
+        _ => return None,
+    };
+    Some(mnemonic)
+}
+
+fn mem_mnemonic(insn: &bpf_insn) -> Option<&'static str> {
+    let code = insn.code;
+    let mnemonic = match (class(code), mode(code)) {
+        (libbpf_sys::BPF_LD, libbpf_sys::BPF_ABS) => "ldabs",
+        (libbpf_sys::BPF_LD, libbpf_sys::BPF_IND) => "ldind",
+        (libbpf_sys::BPF_LDX, libbpf_sys::BPF_MEM) => "ldx",
+        (libbpf_sys::BPF_LDX, libbpf_sys::BPF_MEMSX) => "ldxs",
+        (libbpf_sys::BPF_ST, libbpf_sys::BPF_MEM) => "st",
+        (libbpf_sys::BPF_STX, libbpf_sys::BPF_MEM) => "stx",
+        (libbpf_sys::BPF_STX, libbpf_sys::BPF_ATOMIC) => match insn.imm as u32 {
+            libbpf_sys::BPF_ADD => "atomic_add",
+            libbpf_sys::BPF_AND => "atomic_and",
+            libbpf_sys::BPF_OR => "atomic_or",
+            libbpf_sys::BPF_XOR => "atomic_xor",
+            imm if imm == libbpf_sys::BPF_ADD | libbpf_sys::BPF_FETCH => "atomic_fetch_add",
+            imm if imm == libbpf_sys::BPF_AND | libbpf_sys::BPF_FETCH => "atomic_fetch_and",
+            imm if imm == libbpf_sys::BPF_OR | libbpf_sys::BPF_FETCH => "atomic_fetch_or",
+            imm if imm == libbpf_sys::BPF_XOR | libbpf_sys::BPF_FETCH => "atomic_fetch_xor",
+            libbpf_sys::BPF_XCHG => "atomic_xchg",
+            libbpf_sys::BPF_CMPXCHG => "atomic_cmpxchg",
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(mnemonic)
+}
+
+/// Decode the instruction at the start of `insns`.
+fn decode_insn(idx: usize, insns: &[bpf_insn]) -> DecodedInsn {
+    let insn = &insns[0];
+    let code = insn.code;
+    let mut imm = i64::from(insn.imm);
+    let mnemonic = match class(code) {
+        libbpf_sys::BPF_ALU | libbpf_sys::BPF_ALU64 => alu_mnemonic(insn),
+        libbpf_sys::BPF_JMP | libbpf_sys::BPF_JMP32 => jmp_mnemonic(insn),
+        libbpf_sys::BPF_LD
+            if mode(code) == libbpf_sys::BPF_IMM && size(code) == libbpf_sys::BPF_DW =>
+        {
+            insns.get(1).map(|next| {
+                imm = (u64::from(next.imm as u32) << 32 | u64::from(insn.imm as u32)) as i64;
+                "lddw"
+            })
+        }
+        _ => mem_mnemonic(insn),
+    };
+
+    DecodedInsn {
+        idx,
+        opcode: code,
+        class: InsnClass::from_code(code),
+        mnemonic: mnemonic.unwrap_or("invalid"),
+        dst_reg: insn.dst_reg(),
+        src_reg: insn.src_reg(),
+        off: insn.off,
+        imm,
+    }
+}
+
+/// Decode `insns`, combining the two slots of 64 bit immediate loads.
+pub(crate) fn decode(insns: &[bpf_insn]) -> impl Iterator<Item = DecodedInsn> + '_ {
+    let mut idx = 0;
+    iter::from_fn(move || {
+        let insn = decode_insn(idx, insns.get(idx..).filter(|rest| !rest.is_empty())?);
+        idx += insn.slots();
+        Some(insn)
+    })
+}
+
+/// Disassemble the instruction at the start of `insns`, returning its
+/// textual representation and the number of instruction slots it occupies.
+fn disassemble_insn(insns: &[bpf_insn]) -> (String, usize) {
+    let insn = &insns[0];
+    let code = insn.code;
+    let result = match class(code) {
+        libbpf_sys::BPF_ALU | libbpf_sys::BPF_ALU64 => disassemble_alu(insn).map(|text| (text, 1)),
+        libbpf_sys::BPF_JMP | libbpf_sys::BPF_JMP32 => disassemble_jmp(insn).map(|text| (text, 1)),
+        libbpf_sys::BPF_LD
+            if mode(code) == libbpf_sys::BPF_IMM && size(code) == libbpf_sys::BPF_DW =>
+        {
+            insns
+                .get(1)
+                .map(|next| (disassemble_ld_imm64(insn, next), 2))
+        }
+        _ => disassemble_mem(insn).map(|text| (text, 1)),
+    };
+    result.unwrap_or_else(|| (format!("invalid instruction {code:#04x}"), 1))
+}
+
+/// Disassemble `insns` into one line of text per instruction.
+pub(crate) fn disassemble(insns: &[bpf_insn]) -> String {
+    let mut output = String::new();
+    let mut idx = 0;
+    while idx < insns.len() {
+        let (text, count) = disassemble_insn(&insns[idx..]);
+        let _ = writeln!(output, "{idx:4}: ({:02x}) {text}", insns[idx].code);
+        idx += count;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insn(code: u32, dst: u8, src: u8, off: i16, imm: i32) -> bpf_insn {
+        let mut insn = bpf_insn {
+            code: code as _,
+            off,
+            imm,
+            ..Default::default()
+        };
+        let () = insn.set_dst_reg(dst);
+        let () = insn.set_src_reg(src);
+        insn
+    }
+
+    fn disasm_one(insns: &[bpf_insn]) -> String {
+        disassemble_insn(insns).0
+    }
+
+    /// Check that we can disassemble ALU instructions.
+    #[test]
+    fn disassemble_alu() {
+        use libbpf_sys::*;
+
+        let text = disasm_one(&[insn(BPF_ALU64 | BPF_MOV | BPF_K, 0, 0, 0, 0)]);
+        assert_eq!(text, "r0 = 0");
+        let text = disasm_one(&[insn(BPF_ALU64 | BPF_MOV | BPF_X, 1, 2, 0, 0)]);
+        assert_eq!(text, "r1 = r2");
+        let text = disasm_one(&[insn(BPF_ALU | BPF_ADD | BPF_K, 3, 0, 0, -5)]);
+        assert_eq!(text, "w3 += -5");
+        let text = disasm_one(&[insn(BPF_ALU64 | BPF_DIV | BPF_X, 1, 2, 1, 0)]);
+        assert_eq!(text, "r1 s/= r2");
+        let text = disasm_one(&[insn(BPF_ALU64 | BPF_NEG, 4, 0, 0, 0)]);
+        assert_eq!(text, "r4 = -r4");
+        let text = disasm_one(&[insn(BPF_ALU | BPF_END | BPF_TO_BE, 1, 0, 0, 16)]);
+        assert_eq!(text, "r1 = be16 r1");
+        let text = disasm_one(&[insn(BPF_ALU64 | BPF_MOV | BPF_X, 1, 2, 8, 0)]);
+        assert_eq!(text, "r1 = (s8)r2");
+    }
+
+    /// Check that we can disassemble jump instructions.
+    #[test]
+    fn disassemble_jmp() {
+        use libbpf_sys::*;
+
+        let text = disasm_one(&[insn(BPF_JMP | BPF_EXIT, 0, 0, 0, 0)]);
+        assert_eq!(text, "exit");
+        let text = disasm_one(&[insn(BPF_JMP | BPF_JA, 0, 0, -3, 0)]);
+        assert_eq!(text, "goto pc-3");
+        let text = disasm_one(&[insn(BPF_JMP | BPF_JEQ | BPF_K, 1, 0, 2, 16)]);
+        assert_eq!(text, "if r1 == 0x10 goto pc+2");
+        let text = disasm_one(&[insn(BPF_JMP32 | BPF_JSGT | BPF_X, 1, 2, 1, 0)]);
+        assert_eq!(text, "if w1 s> w2 goto pc+1");
+        let text = disasm_one(&[insn(
+            BPF_JMP | BPF_CALL,
+            0,
+            0,
+            0,
+            BPF_FUNC_map_lookup_elem as _,
+        )]);
+        assert_eq!(text, "call bpf_map_lookup_elem#1");
+        let text = disasm_one(&[insn(BPF_JMP | BPF_CALL, 0, BPF_PSEUDO_CALL as _, 0, 5)]);
+        assert_eq!(text, "call pc+5");
+    }
+
+    /// Check that we can disassemble load and store instructions.
+    #[test]
+    fn disassemble_mem() {
+        use libbpf_sys::*;
+
+        let insns = [
+            insn(BPF_LD | BPF_IMM | BPF_DW, 1, 0, 0, 0x5678),
+            insn(0, 0, 0, 0, 0x1234),
+        ];
+        assert_eq!(
+            disassemble_insn(&insns),
+            ("r1 = 0x123400005678 ll".to_string(), 2)
+        );
+
+        let insns = [
+            insn(BPF_LD | BPF_IMM | BPF_DW, 1, BPF_PSEUDO_MAP_FD as _, 0, 7),
+            insn(0, 0, 0, 0, 0),
+        ];
+        assert_eq!(disasm_one(&insns), "r1 = map[fd:7]");
+
+        let text = disasm_one(&[insn(BPF_LDX | BPF_MEM | BPF_W, 0, 1, 8, 0)]);
+        assert_eq!(text, "r0 = *(u32 *)(r1 +8)");
+        let text = disasm_one(&[insn(BPF_STX | BPF_MEM | BPF_DW, 10, 1, -8, 0)]);
+        assert_eq!(text, "*(u64 *)(r10 -8) = r1");
+        let text = disasm_one(&[insn(BPF_ST | BPF_MEM | BPF_B, 10, 0, -1, 42)]);
+        assert_eq!(text, "*(u8 *)(r10 -1) = 42");
+        let text = disasm_one(&[insn(BPF_STX | BPF_ATOMIC | BPF_DW, 1, 2, 0, BPF_ADD as _)]);
+        assert_eq!(text, "lock *(u64 *)(r1 +0) += r2");
+        let text = disasm_one(&[insn(
+            BPF_STX | BPF_ATOMIC | BPF_W,
+            1,
+            2,
+            0,
+            (BPF_ADD | BPF_FETCH) as _,
+        )]);
+        assert_eq!(text, "w2 = atomic_fetch_add((u32 *)(r1 +0), w2)");
+    }
+
+    /// Check that we can decode instructions, including 64 bit immediate
+    /// loads.
+    #[test]
+    fn decode_insns() {
+        use libbpf_sys::*;
+
+        let insns = [
+            insn(BPF_LD | BPF_IMM | BPF_DW, 1, BPF_PSEUDO_MAP_FD as _, 0, 3),
+            insn(0, 0, 0, 0, 0),
+            insn(BPF_ALU | BPF_ADD | BPF_K, 2, 0, 0, -1),
+            insn(BPF_JMP | BPF_CALL, 0, 0, 0, BPF_FUNC_map_lookup_elem as _),
+            insn(BPF_JMP32 | BPF_JEQ | BPF_X, 1, 2, 4, 0),
+            insn(BPF_JMP | BPF_EXIT, 0, 0, 0, 0),
+        ];
+        let decoded = decode(&insns).collect::<Vec<_>>();
+        let mnemonics = decoded.iter().map(|insn| insn.mnemonic).collect::<Vec<_>>();
+        assert_eq!(mnemonics, ["lddw", "add32", "call", "jeq32", "exit"]);
+        let indices = decoded.iter().map(|insn| insn.idx).collect::<Vec<_>>();
+        assert_eq!(indices, [0, 2, 3, 4, 5]);
+
+        assert_eq!(decoded[0].class, InsnClass::Ld);
+        assert_eq!(decoded[0].src_reg, BPF_PSEUDO_MAP_FD as u8);
+        assert_eq!(decoded[0].imm, 3);
+        assert_eq!(decoded[1].class, InsnClass::Alu);
+        assert_eq!(decoded[1].imm, -1);
+        assert_eq!(decoded[2].helper_id(), Some(BPF_FUNC_map_lookup_elem));
+        assert_eq!(decoded[3].helper_id(), None);
+        assert_eq!(decoded[3].off, 4);
+
+        // A truncated 64 bit immediate load is invalid.
+        let decoded = decode(&insns[..1]).collect::<Vec<_>>();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].mnemonic, "invalid");
+    }
+
+    /// Check that invalid instructions are reported as such.
+    #[test]
+    fn disassemble_invalid() {
+        let insns = [insn(
+            libbpf_sys::BPF_LD | libbpf_sys::BPF_IMM | libbpf_sys::BPF_DW,
+            1,
+            0,
+            0,
+            0,
+        )];
+        assert_eq!(disasm_one(&insns), "invalid instruction 0x18");
+
+        let text = disassemble(&[
+            insn(
+                libbpf_sys::BPF_ALU64 | libbpf_sys::BPF_MOV | libbpf_sys::BPF_K,
+                0,
+                0,
+                0,
+                0,
+            ),
+            insn(libbpf_sys::BPF_JMP | libbpf_sys::BPF_EXIT, 0, 0, 0, 0),
+        ]);
+        assert_eq!(text, "   0: (b7) r0 = 0\n   1: (95) exit\n");
+    }
+}
@@ -0,0 +1,365 @@
+//! A minimal ELF reader, sufficient for locating USDT probes.
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::Result;
+
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const ELFDATA2MSB: u8 = 2;
+const PT_LOAD: u32 = 1;
+const SHT_NOTE: u32 = 7;
+const NT_STAPSDT: u32 = 3;
+
+fn truncated() -> Error {
+    Error::with_invalid_data("ELF file is truncated")
+}
+
+/// Add two offsets or sizes read from the file. Overflow can only result
+/// from a malformed file.
+fn add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or_else(truncated)
+}
+
+/// Multiply two offsets or sizes read from the file. Overflow can only
+/// result from a malformed file.
+fn mul(a: u64, b: u64) -> Result<u64> {
+    a.checked_mul(b).ok_or_else(truncated)
+}
+
+/// The location of a USDT probe inside of an ELF file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct UsdtNote {
+    /// The file offset of the probe's fire site.
+    pub pc_offset: usize,
+    /// The file offset of the probe's semaphore, or `0` if it has none.
+    pub sema_offset: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Section {
+    name: u32,
+    type_: u32,
+    addr: u64,
+    offset: u64,
+    size: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    type_: u32,
+    offset: u64,
+    vaddr: u64,
+    memsz: u64,
+}
+
+struct Elf<'dat> {
+    data: &'dat [u8],
+    is_64: bool,
+    is_le: bool,
+}
+
+impl<'dat> Elf<'dat> {
+    fn parse(data: &'dat [u8]) -> Result<Self> {
+        if data.get(0..4) != Some(b"\x7fELF") {
+            return Err(Error::with_invalid_data("file is not an ELF file"));
+        }
+
+        let is_64 = match data.get(4) {
+            Some(&ELFCLASS32) => false,
+            Some(&ELFCLASS64) => true,
+            _ => return Err(Error::with_invalid_data("invalid ELF class")),
+        };
+        let is_le = match data.get(5) {
+            Some(&ELFDATA2LSB) => true,
+            Some(&ELFDATA2MSB) => false,
+            _ => return Err(Error::with_invalid_data("invalid ELF data encoding")),
+        };
+
+        Ok(Self { data, is_64, is_le })
+    }
+
+    fn bytes(&self, range: Range<u64>) -> Result<&'dat [u8]> {
+        let start = usize::try_from(range.start).ok();
+        let end = usize::try_from(range.end).ok();
+        start
+            .zip(end)
+            .and_then(|(start, end)| self.data.get(start..end))
+            .ok_or_else(truncated)
+    }
+
+    fn array<const N: usize>(&self, offset: u64) -> Result<[u8; N]> {
+        let bytes = self.bytes(offset..add(offset, N as u64)?)?;
+        // `bytes` is guaranteed to be of length `N`.
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16> {
+        let bytes = self.array(offset)?;
+        Ok(if self.is_le {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32> {
+        let bytes = self.array(offset)?;
+        Ok(if self.is_le {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64> {
+        let bytes = self.array(offset)?;
+        Ok(if self.is_le {
+            u64::from_le_bytes(bytes)
+        } else {
+            u64::from_be_bytes(bytes)
+        })
+    }
+
+    /// Read a class-sized value, e.g., an address.
+    fn word(&self, offset: u64) -> Result<u64> {
+        if self.is_64 {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+
+    fn word_size(&self) -> u64 {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn sections(&self) -> Result<Vec<Section>> {
+        let (shoff, shentsize, shnum) = if self.is_64 {
+            (self.u64(0x28)?, self.u16(0x3a)?, self.u16(0x3c)?)
+        } else {
+            (u64::from(self.u32(0x20)?), self.u16(0x2e)?, self.u16(0x30)?)
+        };
+
+        (0..u64::from(shnum))
+            .map(|idx| {
+                let hdr = add(shoff, mul(idx, u64::from(shentsize))?)?;
+                let section = if self.is_64 {
+                    Section {
+                        name: self.u32(hdr)?,
+                        type_: self.u32(add(hdr, 0x4)?)?,
+                        addr: self.u64(add(hdr, 0x10)?)?,
+                        offset: self.u64(add(hdr, 0x18)?)?,
+                        size: self.u64(add(hdr, 0x20)?)?,
+                    }
+                } else {
+                    Section {
+                        name: self.u32(hdr)?,
+                        type_: self.u32(add(hdr, 0x4)?)?,
+                        addr: self.u32(add(hdr, 0xc)?)?.into(),
+                        offset: self.u32(add(hdr, 0x10)?)?.into(),
+                        size: self.u32(add(hdr, 0x14)?)?.into(),
+                    }
+                };
+                Ok(section)
+            })
+            .collect()
+    }
+
+    fn segments(&self) -> Result<Vec<Segment>> {
+        let (phoff, phentsize, phnum) = if self.is_64 {
+            (self.u64(0x20)?, self.u16(0x36)?, self.u16(0x38)?)
+        } else {
+            (u64::from(self.u32(0x1c)?), self.u16(0x2a)?, self.u16(0x2c)?)
+        };
+
+        (0..u64::from(phnum))
+            .map(|idx| {
+                let hdr = add(phoff, mul(idx, u64::from(phentsize))?)?;
+                let segment = if self.is_64 {
+                    Segment {
+                        type_: self.u32(hdr)?,
+                        offset: self.u64(add(hdr, 0x8)?)?,
+                        vaddr: self.u64(add(hdr, 0x10)?)?,
+                        memsz: self.u64(add(hdr, 0x28)?)?,
+                    }
+                } else {
+                    Segment {
+                        type_: self.u32(hdr)?,
+                        offset: self.u32(add(hdr, 0x4)?)?.into(),
+                        vaddr: self.u32(add(hdr, 0x8)?)?.into(),
+                        memsz: self.u32(add(hdr, 0x14)?)?.into(),
+                    }
+                };
+                Ok(segment)
+            })
+            .collect()
+    }
+
+    fn section_name(&self, sections: &[Section], section: &Section) -> Result<&'dat [u8]> {
+        let shstrndx = if self.is_64 {
+            self.u16(0x3e)?
+        } else {
+            self.u16(0x32)?
+        };
+        let strtab = sections
+            .get(usize::from(shstrndx))
+            .ok_or_else(|| Error::with_invalid_data("invalid ELF section name table index"))?;
+        let names = self.bytes(strtab.offset..add(strtab.offset, strtab.size)?)?;
+        let name = names
+            .get(section.name as usize..)
+            .and_then(|name| name.split(|b| *b == 0).next())
+            .ok_or_else(|| Error::with_invalid_data("invalid ELF section name"))?;
+        Ok(name)
+    }
+
+    /// Convert a virtual address into a file offset, based on the loadable
+    /// segment containing it.
+    fn addr_to_offset(segments: &[Segment], addr: u64) -> Result<usize> {
+        segments
+            .iter()
+            .filter(|segment| segment.type_ == PT_LOAD)
+            .find(|segment| {
+                segment.vaddr <= addr
+                    && segment
+                        .vaddr
+                        .checked_add(segment.memsz)
+                        .is_some_and(|end| addr < end)
+            })
+            .and_then(|segment| {
+                (addr - segment.vaddr)
+                    .checked_add(segment.offset)
+                    .and_then(|offset| usize::try_from(offset).ok())
+            })
+            .ok_or_else(|| {
+                Error::with_invalid_data(format!(
+                    "address {addr:#x} is not contained in any loadable segment"
+                ))
+            })
+    }
+}
+
+/// Split off a NUL terminated string from the front of `data`.
+fn split_cstr(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let nul_idx = data.iter().position(|b| *b == 0)?;
+    Some((&data[..nul_idx], &data[nul_idx + 1..]))
+}
+
+fn align4(value: u64) -> u64 {
+    (value + 3) & !3
+}
+
+/// Find the USDT probe `provider:name` described in the `.note.stapsdt`
+/// section of the ELF file at `path`.
+///
+/// If the probe has multiple sites, the first one is reported.
+pub(crate) fn find_usdt_note(path: &Path, provider: &str, name: &str) -> Result<UsdtNote> {
+    let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let elf = Elf::parse(&data).with_context(|| format!("failed to parse {}", path.display()))?;
+    let sections = elf.sections()?;
+    let segments = elf.segments()?;
+
+    let mut notes = None;
+    let mut base_addr = None;
+    for section in &sections {
+        match elf.section_name(&sections, section)? {
+            b".note.stapsdt" if section.type_ == SHT_NOTE => notes = Some(*section),
+            b".stapsdt.base" => base_addr = Some(section.addr),
+            _ => (),
+        }
+    }
+
+    let notes = notes.ok_or_else(|| {
+        Error::with_io_error(
+            io::ErrorKind::NotFound,
+            format!("no `.note.stapsdt` section found in {}", path.display()),
+        )
+    })?;
+
+    let word_size = elf.word_size();
+    let end = add(notes.offset, notes.size)?;
+    let mut offset = notes.offset;
+    while add(offset, 12)? <= end {
+        let namesz = u64::from(elf.u32(offset)?);
+        let descsz = u64::from(elf.u32(add(offset, 4)?)?);
+        let type_ = elf.u32(add(offset, 8)?)?;
+        let name_off = add(offset, 12)?;
+        let desc_off = add(name_off, align4(namesz))?;
+        let desc_end = add(desc_off, descsz)?;
+        offset = add(desc_off, align4(descsz))?;
+
+        if type_ != NT_STAPSDT || elf.bytes(name_off..add(name_off, namesz)?)? != b"stapsdt\0" {
+            continue;
+        }
+
+        let mut pc = elf.word(desc_off)?;
+        let note_base = elf.word(add(desc_off, word_size)?)?;
+        let mut sema = elf.word(add(desc_off, 2 * word_size)?)?;
+        let strs = elf.bytes(add(desc_off, 3 * word_size)?..desc_end)?;
+        let invalid = || Error::with_invalid_data("invalid USDT note");
+        let (note_provider, strs) = split_cstr(strs).ok_or_else(invalid)?;
+        let (note_name, _args) = split_cstr(strs).ok_or_else(invalid)?;
+
+        if note_provider != provider.as_bytes() || note_name != name.as_bytes() {
+            continue;
+        }
+
+        // Adjust for prelinking, which may have moved the binary without
+        // updating the notes.
+        if let Some(base_addr) = base_addr {
+            if note_base != 0 {
+                pc = pc.wrapping_add(base_addr).wrapping_sub(note_base);
+                if sema != 0 {
+                    sema = sema.wrapping_add(base_addr).wrapping_sub(note_base);
+                }
+            }
+        }
+
+        let pc_offset = Elf::addr_to_offset(&segments, pc)?;
+        let sema_offset = if sema != 0 {
+            Elf::addr_to_offset(&segments, sema)?
+        } else {
+            0
+        };
+        return Ok(UsdtNote {
+            pc_offset,
+            sema_offset,
+        });
+    }
+
+    Err(Error::with_io_error(
+        io::ErrorKind::NotFound,
+        format!(
+            "USDT probe `{provider}:{name}` not found in {}",
+            path.display()
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::current_exe;
+
+    /// Check that we fail to find USDT probes in files without any.
+    #[test]
+    fn usdt_note_absent() {
+        let err = find_usdt_note(Path::new("/proc/self/cmdline"), "foo", "bar").unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidData);
+
+        let exe = current_exe().unwrap();
+        let err = find_usdt_note(&exe, "does_not_exist", "nope").unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::NotFound);
+    }
+}
@@ -79,6 +79,8 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
 pub mod btf;
+mod disasm;
+mod elf;
 mod error;
 mod iter;
 mod link;
@@ -100,6 +102,8 @@ pub use libbpf_sys;
 pub use crate::btf::Btf;
 pub use crate::btf::HasSize;
 pub use crate::btf::ReferencesType;
+pub use crate::disasm::DecodedInsn;
+pub use crate::disasm::InsnClass;
 pub use crate::error::Error;
 pub use crate::error::ErrorExt;
 pub use crate::error::ErrorKind;
@@ -123,17 +127,38 @@ pub use crate::print::get_print;
 pub use crate::print::set_print;
 pub use crate::print::PrintCallback;
 pub use crate::print::PrintLevel;
+pub use crate::program::CgroupAttachFlags;
+pub use crate::program::CgroupAttachOpts;
+pub use crate::program::CgroupIterOrder;
 pub use crate::program::Input as ProgramInput;
+pub use crate::program::InsnCounts;
+pub use crate::program::IterOpts;
+pub use crate::program::KprobeMultiOpts;
+pub use crate::program::KprobeOpts;
+pub use crate::program::KsyscallOpts;
+pub use crate::program::NetfilterOpts;
 pub use crate::program::OpenProgram;
 pub use crate::program::Output as ProgramOutput;
+pub use crate::program::PerfEventConfig;
+pub use crate::program::PerfEventOpts;
+pub use crate::program::PerfEventSampling;
+pub use crate::program::ProbeAttachMode;
 pub use crate::program::Program;
 pub use crate::program::ProgramAttachType;
 pub use crate::program::ProgramType;
+pub use crate::program::TcxAttachPoint;
+pub use crate::program::TcxChain;
+pub use crate::program::TcxOpts;
+pub use crate::program::TestRunner;
+pub use crate::program::TraceOpts;
 pub use crate::program::TracepointOpts;
+pub use crate::program::UprobeMultiOpts;
 pub use crate::program::UprobeOpts;
 pub use crate::program::UsdtOpts;
+pub use crate::program::XdpOpts;
 pub use crate::ringbuf::RingBuffer;
 pub use crate::ringbuf::RingBufferBuilder;
+pub use crate::tc::SkBuffContext;
 pub use crate::tc::TcAttachPoint;
 pub use crate::tc::TcHook;
 pub use crate::tc::TcHookBuilder;
@@ -144,8 +169,10 @@ pub use crate::tc::TC_H_INGRESS;
 pub use crate::tc::TC_H_MIN_EGRESS;
 pub use crate::tc::TC_H_MIN_INGRESS;
 pub use crate::tc::TC_INGRESS;
+pub use crate::util::enable_stats;
 pub use crate::util::num_possible_cpus;
 pub use crate::xdp::Xdp;
+pub use crate::xdp::XdpContext;
 pub use crate::xdp::XdpFlags;
 
 /// Used for skeleton -- an end user may not consider this API stable
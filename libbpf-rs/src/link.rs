@@ -1,13 +1,22 @@
 use std::fmt::Debug;
+use std::mem::size_of_val;
 use std::os::unix::io::AsFd;
+use std::os::unix::io::AsRawFd;
 use std::os::unix::io::BorrowedFd;
 use std::path::Path;
 use std::path::PathBuf;
+use std::ptr;
 use std::ptr::NonNull;
 
+use crate::query::LinkInfo;
 use crate::util;
 use crate::AsRawLibbpf;
+use crate::Error;
+use crate::ErrorExt as _;
+use crate::ErrorKind;
 use crate::Program;
+use crate::ProgramAttachType;
+use crate::ProgramType;
 use crate::Result;
 
 /// Represents an attached [`Program`].
@@ -17,6 +26,7 @@ use crate::Result;
 #[derive(Debug)]
 pub struct Link {
     ptr: NonNull<libbpf_sys::bpf_link>,
+    cookie: u64,
 }
 
 impl Link {
@@ -26,10 +36,27 @@ impl Link {
     ///
     /// `ptr` must point to a correctly initialized [`libbpf_sys::bpf_link`].
     pub(crate) unsafe fn new(ptr: NonNull<libbpf_sys::bpf_link>) -> Self {
-        Link { ptr }
+        unsafe { Self::new_with_cookie(ptr, 0) }
+    }
+
+    /// Create a new [`Link`] from a [`libbpf_sys::bpf_link`] that was
+    /// attached with the provided BPF cookie.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a correctly initialized [`libbpf_sys::bpf_link`].
+    pub(crate) unsafe fn new_with_cookie(ptr: NonNull<libbpf_sys::bpf_link>, cookie: u64) -> Self {
+        Link { ptr, cookie }
     }
 
     /// Create link from BPF FS file.
+    ///
+    /// Any link pinned via [`Link::pin`] (or by other means, e.g., by
+    /// another process) can be opened, independent of its type. The link
+    /// stays attached for as long as it is pinned; dropping the returned
+    /// object merely closes the file descriptor, while [`Link::unpin`]
+    /// followed by dropping it detaches the link, as usual. Note that the
+    /// BPF cookie the link was created with is unknown, see [`Link::cookie`].
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path_c = util::path_to_cstring(path)?;
         let path_ptr = path_c.as_ptr();
@@ -47,10 +74,83 @@ impl Link {
     }
 
     /// Replace the underlying prog with `prog`.
+    ///
+    /// This is the same as [`Link::update_program`].
     pub fn update_prog(&mut self, prog: &Program) -> Result<()> {
-        let ret =
-            unsafe { libbpf_sys::bpf_link__update_program(self.ptr.as_ptr(), prog.ptr.as_ptr()) };
-        util::parse_ret(ret)
+        self.update_program(prog)
+    }
+
+    /// Atomically replace the program this link is attached with by `prog`,
+    /// without detaching it in between.
+    ///
+    /// For link types recording what they are attached to, `prog` is
+    /// checked against it first: XDP, netfilter, and TCX links require a
+    /// program of the respective type, and cgroup, network namespace, and
+    /// TCX links a program with their attach type. A mismatch is reported
+    /// as an error of kind [`ErrorKind::InvalidData`]. The type of the
+    /// currently attached program is not inspected, as doing so requires
+    /// `CAP_SYS_ADMIN`; the kernel still rejects programs of a different
+    /// type. Not all types of links support updating their program, in
+    /// which case an error of kind [`ErrorKind::InvalidInput`] is reported.
+    pub fn update_program(&mut self, prog: &Program) -> Result<()> {
+        let mut link_info = libbpf_sys::bpf_link_info::default();
+        let mut len = size_of_val(&link_info) as u32;
+        let ret = unsafe {
+            libbpf_sys::bpf_link_get_info_by_fd(self.as_fd().as_raw_fd(), &mut link_info, &mut len)
+        };
+        let () = util::parse_ret(ret).context("failed to retrieve link information")?;
+
+        let (exp_ty, exp_attach_type) = unsafe {
+            // SAFETY: the union member accessed matches the link type.
+            match link_info.type_ {
+                libbpf_sys::BPF_LINK_TYPE_XDP => (Some(ProgramType::Xdp), None),
+                libbpf_sys::BPF_LINK_TYPE_NETFILTER => (Some(ProgramType::Netfilter), None),
+                libbpf_sys::BPF_LINK_TYPE_TCX => (
+                    Some(ProgramType::SchedCls),
+                    Some(link_info.__bindgen_anon_1.tcx.attach_type),
+                ),
+                libbpf_sys::BPF_LINK_TYPE_CGROUP => {
+                    (None, Some(link_info.__bindgen_anon_1.cgroup.attach_type))
+                }
+                libbpf_sys::BPF_LINK_TYPE_NETNS => {
+                    (None, Some(link_info.__bindgen_anon_1.netns.attach_type))
+                }
+                _ => (None, None),
+            }
+        };
+
+        if let Some(exp_ty) = exp_ty {
+            let ty = prog.prog_type();
+            if ty as u32 != exp_ty as u32 {
+                return Err(Error::with_invalid_data(format!(
+                    "program type ({ty}) does not match type of linked program ({exp_ty})"
+                )));
+            }
+        }
+        if let Some(exp_attach_type) = exp_attach_type {
+            let attach_type = prog.attach_type();
+            if attach_type.clone() as u32 != exp_attach_type {
+                let exp_attach_type = ProgramAttachType::try_from(exp_attach_type)
+                    .unwrap_or(ProgramAttachType::Unknown);
+                return Err(Error::with_invalid_data(format!(
+                    "program attach type ({attach_type}) does not match that of link ({exp_attach_type})"
+                )));
+            }
+        }
+
+        let ret = unsafe {
+            libbpf_sys::bpf_link_update(
+                self.as_fd().as_raw_fd(),
+                prog.as_fd().as_raw_fd(),
+                ptr::null(),
+            )
+        };
+        util::parse_ret(ret).map_err(|err| match err.kind() {
+            ErrorKind::InvalidInput => {
+                err.context("link does not support updating its program with the provided one")
+            }
+            _ => err,
+        })
     }
 
     /// Release "ownership" of underlying BPF resource (typically, a BPF program
@@ -103,6 +203,24 @@ impl Link {
         let ret = unsafe { libbpf_sys::bpf_link__detach(self.ptr.as_ptr()) };
         util::parse_ret(ret)
     }
+
+    /// Retrieve information about the link from the kernel, e.g., its ID,
+    /// the ID of the program it is attached with, and, depending on its
+    /// type, its attach target.
+    pub fn info(&self) -> Result<LinkInfo> {
+        LinkInfo::load_from_fd(self.as_fd())
+    }
+
+    /// Retrieve the BPF cookie provided when attaching, i.e., the value
+    /// reported by `bpf_get_attach_cookie` inside the program.
+    ///
+    /// libbpf does not keep track of cookies, so this is the value passed to
+    /// one of the `*_with_opts` attach methods of [`Program`]. It is `0` if
+    /// no cookie was provided, including for links opened via
+    /// [`Link::open`] or [`Link::from_ptr`].
+    pub fn cookie(&self) -> u64 {
+        self.cookie
+    }
 }
 
 impl AsRawLibbpf for Link {
@@ -2,15 +2,21 @@ use core::ffi::c_void;
 use std::collections::HashMap;
 use std::ffi::CStr;
 use std::ffi::CString;
+use std::fmt::Debug;
+use std::io;
 use std::mem;
+use std::os::fd::AsFd as _;
+use std::os::fd::OwnedFd;
 use std::path::Path;
 use std::ptr;
 use std::ptr::NonNull;
 
+use crate::print::capture_prog_load_logs;
 use crate::set_print;
 use crate::util;
 use crate::Btf;
 use crate::Error;
+use crate::ErrorExt as _;
 use crate::Map;
 use crate::OpenMap;
 use crate::OpenProgram;
@@ -121,7 +127,6 @@ impl ObjectBuilder {
     pub fn open_file<P: AsRef<Path>>(&mut self, path: P) -> Result<OpenObject> {
         let path_c = util::path_to_cstring(path)?;
         let path_ptr = path_c.as_ptr();
-
         let opts = self.opts();
 
         util::create_bpf_entity_checked(|| unsafe {
@@ -232,8 +237,12 @@ impl OpenObject {
             // using destructuring we make sure we'll get a compiler error if anything in
             // Self changes, which will alert us to change this function as well
             let Self { ptr, maps, progs } = &mut self;
+            // libbpf refers to log buffers until the object is loaded, which may now
+            // happen outside of our control. Leak them to be on the safe side.
+            mem::take(progs)
+                .into_values()
+                .for_each(|mut prog| mem::forget(prog.log_buf.take()));
             mem::take(maps);
-            mem::take(progs);
             *ptr
         };
         // avoid double free of self.ptr
@@ -301,14 +310,85 @@ impl OpenObject {
     }
 
     /// Load the maps and programs contained in this BPF object into the system.
+    ///
+    /// If loading fails because the kernel rejects a program, the verifier log is included in
+    /// the returned error, provided the program does not have its own log buffer set.
     pub fn load(self) -> Result<Object> {
-        let ret = unsafe { libbpf_sys::bpf_object__load(self.ptr.as_ptr()) };
-        let () = util::parse_ret(ret)?;
-
-        let obj = unsafe { Object::from_ptr(self.take_ptr())? };
+        let (result, _logs) = self.load_impl();
+        result
+    }
+
+    /// Load the object, additionally reporting the verifier logs libbpf
+    /// reported while doing so, as pairs of program name and log.
+    fn load_impl(self) -> (Result<Object>, Vec<(String, String)>) {
+        // libbpf manages the verifier log buffers itself, growing them as
+        // necessary, and reports logs via the print callback.
+        let (ret, mut logs) =
+            capture_prog_load_logs(|| unsafe { libbpf_sys::bpf_object__load(self.ptr.as_ptr()) });
+        if let Err(err) = util::parse_ret(ret) {
+            // libbpf stops at the first program failing to load, so its log
+            // is the last one reported.
+            let err = match logs.pop() {
+                Some((_, log)) if !log.trim().is_empty() => {
+                    err.context(format!("failed to load object; verifier log:\n{log}"))
+                }
+                _ => err,
+            };
+            return (Err(err), logs);
+        }
+        let result = self.load_finish();
+        (result, logs)
+    }
+
+    fn load_finish(mut self) -> Result<Object> {
+        // Hand over program log buffers to the loaded programs.
+        let log_bufs = self
+            .progs
+            .iter_mut()
+            .filter_map(|(name, prog)| Some((name.clone(), prog.log_buf.take()?)))
+            .collect::<Vec<_>>();
+
+        let mut obj = unsafe { Object::from_ptr(self.take_ptr())? };
+        for (name, log_buf) in log_bufs {
+            if let Some(prog) = obj.progs.get_mut(&name) {
+                prog.log_buf = Some(log_buf);
+            }
+        }
 
         Ok(obj)
     }
+
+    /// Load the object like [`OpenObject::load`], capturing the verifier log
+    /// of the program `prog_name` at log level `log_level` (see
+    /// [`OpenProgram::set_log_level`]).
+    ///
+    /// On success, the loaded object is returned along with the log. On
+    /// failure, the log of the program failing to load is included in the
+    /// error's context.
+    ///
+    /// libbpf manages the log buffer, growing it as necessary. The program
+    /// must not have its own log buffer set via
+    /// [`OpenProgram::set_log_buf`], or the log is written there instead.
+    pub fn load_with_log(mut self, prog_name: &str, log_level: u32) -> Result<(Object, String)> {
+        let prog = self.prog_mut(prog_name).ok_or_else(|| {
+            Error::with_io_error(
+                io::ErrorKind::NotFound,
+                format!("no program `{prog_name}` found"),
+            )
+        })?;
+        let () = prog.set_log_level(log_level)?;
+
+        // The error reported on failure already includes the log of the
+        // program failing to load.
+        let (result, logs) = self.load_impl();
+        let obj = result?;
+        let log = logs
+            .into_iter()
+            .rev()
+            .find_map(|(name, log)| (name == prog_name).then_some(log))
+            .unwrap_or_default();
+        Ok((obj, log))
+    }
 }
 
 impl Drop for OpenObject {
@@ -434,6 +514,51 @@ impl Object {
         self.progs.get_mut(name.as_ref())
     }
 
+    /// Unload the `Program` with the name `name` from the kernel, closing its file descriptor.
+    ///
+    /// The program is removed from this object and can no longer be accessed through it. Note
+    /// that the kernel keeps the program alive for as long as it is referenced otherwise, e.g.,
+    /// by a [`Link`][crate::Link] it has been attached with or by a duplicate of its file
+    /// descriptor. Links should therefore be dropped before unloading the program they belong to.
+    pub fn unload_prog<T: AsRef<str>>(&mut self, name: T) -> Result<()> {
+        let name = name.as_ref();
+        let prog = self.progs.remove(name).ok_or_else(|| {
+            Error::with_io_error(
+                io::ErrorKind::NotFound,
+                format!("no program with name `{name}` found"),
+            )
+        })?;
+        let () = prog.unload();
+        Ok(())
+    }
+
+    /// Remove the `Program` with the name `name` from this object, taking
+    /// ownership of a file descriptor referring to it.
+    ///
+    /// The program stays loaded for as long as the returned file descriptor
+    /// (or any other reference) is alive, independent of this object. This
+    /// allows for handing the program to other APIs, e.g., passing it over a
+    /// Unix domain socket, and for controlling when and how it gets closed,
+    /// e.g., to observe errors reported by `close(2)`.
+    ///
+    /// libbpf has no means of relinquishing ownership of a program's file
+    /// descriptor, so it is duplicated and libbpf's copy is closed, which
+    /// does not unload the program.
+    pub fn take_prog_fd<T: AsRef<str>>(&mut self, name: T) -> Result<OwnedFd> {
+        let name = name.as_ref();
+        let prog = self.progs.get(name).ok_or_else(|| {
+            Error::with_io_error(
+                io::ErrorKind::NotFound,
+                format!("no program with name `{name}` found"),
+            )
+        })?;
+        let fd = prog.as_fd().try_clone_to_owned()?;
+        // SANITY: We just looked up the program.
+        let prog = self.progs.remove(name).unwrap();
+        let () = prog.unload();
+        Ok(fd)
+    }
+
     /// Get an iterator over references to all `Program`s.
     pub fn progs_iter(&self) -> impl Iterator<Item = &Program> {
         self.progs.values()
@@ -1,7 +1,11 @@
+use std::cell::Cell;
+use std::cell::RefCell;
 use std::io;
 use std::io::Write;
 use std::mem;
 use std::os::raw::c_char;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Mutex;
 
 use crate::util::LazyLock;
@@ -51,21 +55,91 @@ fn default_callback(_lvl: PrintLevel, msg: String) {
 static PRINT_CB: LazyLock<Mutex<Option<(PrintLevel, PrintCallback)>>> =
     LazyLock::new(|| Mutex::new(Some((PrintLevel::Info, default_callback))));
 
+/// Whether `outer_print_cb` has been registered with libbpf.
+static OUTER_PRINT_CB_SET: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // Whether the current thread is in the process of running the user
+    // provided callback.
+    static IN_PRINT_CB: Cell<bool> = const { Cell::new(false) };
+    // The program load logs captured on the current thread, by program
+    // name, if capturing via `capture_prog_load_logs` is active.
+    static PROG_LOAD_LOGS: RefCell<Option<Vec<(String, String)>>> = const { RefCell::new(None) };
+}
+
+const PROG_LOAD_LOG_BEGIN: &str = "-- BEGIN PROG LOAD LOG --\n";
+const PROG_LOAD_LOG_END: &str = "-- END PROG LOAD LOG --\n";
+
+/// Extract the program name and load log from a libbpf message, if it
+/// contains a load log.
+fn parse_prog_load_log(msg: &str) -> Option<(&str, &str)> {
+    let (prefix, log) = msg.split_once(PROG_LOAD_LOG_BEGIN)?;
+    let (_, name) = prefix.split_once("prog '")?;
+    let (name, _) = name.split_once("': ")?;
+    let log = log.strip_suffix(PROG_LOAD_LOG_END).unwrap_or(log);
+    Some((name, log))
+}
+
+/// Run `f`, capturing the verifier logs libbpf reported on the current
+/// thread meanwhile, as pairs of program name and log, in the order they
+/// were reported.
+///
+/// libbpf reports the log of a program that failed to load as a warning.
+/// The log of a successfully loaded program with a log level set is
+/// reported as debug message.
+pub(crate) fn capture_prog_load_logs<T>(f: impl FnOnce() -> T) -> (T, Vec<(String, String)>) {
+    // The user may never have set a callback, in which case libbpf prints
+    // to stderr itself; our default callback mimics that behavior.
+    if !OUTER_PRINT_CB_SET.swap(true, Ordering::Relaxed) {
+        unsafe { libbpf_sys::libbpf_set_print(Some(outer_print_cb)) };
+    }
+
+    let prev = PROG_LOAD_LOGS.with(|logs| logs.replace(Some(Vec::new())));
+    let result = f();
+    let logs = PROG_LOAD_LOGS
+        .with(|logs| logs.replace(prev))
+        .unwrap_or_default();
+    (result, logs)
+}
+
 extern "C" fn outer_print_cb(
     level: libbpf_sys::libbpf_print_level,
     fmtstr: *const c_char,
     va_list: *mut libbpf_sys::__va_list_tag,
 ) -> i32 {
+    // The callback may itself call into libbpf, which may want to log
+    // something. Drop such nested messages instead of recursing
+    // (potentially without bound).
+    if IN_PRINT_CB.with(|in_cb| in_cb.replace(true)) {
+        return 0;
+    }
+
     let level = level.into();
-    if let Some((min_level, func)) = { *PRINT_CB.lock().unwrap() } {
-        if level <= min_level {
-            let msg = match unsafe { vsprintf::vsprintf(fmtstr, va_list) } {
-                Ok(s) => s,
-                Err(e) => format!("Failed to parse libbpf output: {e}"),
-            };
+    let capture = PROG_LOAD_LOGS.with(|logs| logs.borrow().is_some());
+    // Note that the lock is released before invoking the callback, so that
+    // it is free to call `set_print` or `get_print`.
+    let callback = { *PRINT_CB.lock().unwrap() }.filter(|(min_level, _)| level <= *min_level);
+    if capture || callback.is_some() {
+        let msg = match unsafe { vsprintf::vsprintf(fmtstr, va_list) } {
+            Ok(s) => s,
+            Err(e) => format!("Failed to parse libbpf output: {e}"),
+        };
+        if capture {
+            if let Some((name, log)) = parse_prog_load_log(&msg) {
+                let entry = (name.to_string(), log.to_string());
+                let () = PROG_LOAD_LOGS.with(|logs| {
+                    if let Some(logs) = logs.borrow_mut().as_mut() {
+                        logs.push(entry)
+                    }
+                });
+            }
+        }
+        if let Some((_, func)) = callback {
             func(level, msg);
         }
     }
+
+    let () = IN_PRINT_CB.with(|in_cb| in_cb.set(false));
     0 // return value is ignored by libbpf
 }
 
@@ -78,6 +152,9 @@ extern "C" fn outer_print_cb(
 ///
 /// This overrides (and is overridden by) [`ObjectBuilder::debug`][crate::ObjectBuilder::debug]
 ///
+/// Messages emitted by libbpf while `function` is running on the same thread,
+/// e.g., because it calls into libbpf itself, are dropped.
+///
 /// # Examples
 ///
 /// To pass all messages to the `log` crate:
@@ -115,9 +192,13 @@ extern "C" fn outer_print_cb(
 pub fn set_print(
     mut callback: Option<(PrintLevel, PrintCallback)>,
 ) -> Option<(PrintLevel, PrintCallback)> {
-    let real_cb: libbpf_sys::libbpf_print_fn_t = callback.as_ref().and(Some(outer_print_cb));
     mem::swap(&mut callback, &mut *PRINT_CB.lock().unwrap());
-    unsafe { libbpf_sys::libbpf_set_print(real_cb) };
+    // The callback stays registered with libbpf even if printing is
+    // disabled, so that program load logs can still be captured. It is
+    // registered anew every time, in case anything else replaced it
+    // meanwhile.
+    let _prev = unsafe { libbpf_sys::libbpf_set_print(Some(outer_print_cb)) };
+    let () = OUTER_PRINT_CB_SET.store(true, Ordering::Relaxed);
     callback
 }
 
@@ -138,3 +219,20 @@ pub fn set_print(
 pub fn get_print() -> Option<(PrintLevel, PrintCallback)> {
     *PRINT_CB.lock().unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that we can extract the program load log from libbpf's
+    /// messages.
+    #[test]
+    fn prog_load_log_parsing() {
+        let msg =
+            "libbpf: prog 'foo': -- BEGIN PROG LOAD LOG --\n0: R1=ctx()\n-- END PROG LOAD LOG --\n";
+        assert_eq!(parse_prog_load_log(msg), Some(("foo", "0: R1=ctx()\n")));
+
+        let msg = "libbpf: prog 'foo': BPF program load failed: Invalid argument\n";
+        assert_eq!(parse_prog_load_log(msg), None);
+    }
+}
@@ -1,29 +1,115 @@
+use std::borrow::Cow;
+use std::ffi::c_ulong;
 use std::ffi::c_void;
 use std::ffi::CStr;
+use std::fs;
+use std::fs::Permissions;
+use std::io;
+use std::marker::PhantomData;
 use std::mem;
 use std::mem::size_of;
 use std::mem::size_of_val;
+use std::os::raw::c_char;
+use std::os::unix::fs::PermissionsExt as _;
 use std::os::unix::io::AsFd;
 use std::os::unix::io::AsRawFd;
 use std::os::unix::io::BorrowedFd;
 use std::os::unix::io::FromRawFd;
+use std::os::unix::io::IntoRawFd;
 use std::os::unix::io::OwnedFd;
 use std::path::Path;
+use std::path::PathBuf;
 use std::ptr;
 use std::ptr::NonNull;
 use std::slice;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::SystemTime;
 
+use bitflags::bitflags;
 use libbpf_sys::bpf_func_id;
 use num_enum::TryFromPrimitive;
+#[cfg(feature = "serde")]
+use serde::de::Error as _;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Deserializer;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::Serializer;
 use strum_macros::Display;
 
+use crate::btf::types::Func;
+use crate::disasm;
+use crate::elf;
+use crate::query::BtfInfoIter;
+use crate::query::LineInfo;
+use crate::query::ProgInfoQueryOptions;
+use crate::query::ProgramInfo;
+use crate::query::SourceLine;
+use crate::tc::SK_BUFF_SIZE;
 use crate::util;
 use crate::AsRawLibbpf;
+use crate::Btf;
+use crate::DecodedInsn;
 use crate::Error;
+use crate::ErrorExt as _;
+use crate::ErrorKind;
 use crate::Link;
+use crate::Map;
 use crate::Result;
+use crate::XdpFlags;
+
+/// The mechanism used for attaching a [`Program`] to a kprobe or uprobe.
+///
+/// By default libbpf picks the most capable mechanism the kernel supports:
+/// BPF links for perf events (Linux 5.15), falling back to attaching to a
+/// perf event created via the kprobe/uprobe PMU (Linux 4.17), and
+/// ultimately to creating the probe via tracefs (`kprobe_events` and
+/// `uprobe_events`). Other modes use just the given mechanism, failing
+/// instead of falling back.
+///
+/// Attaching to multiple kprobes or uprobes at once, e.g., via
+/// [`Program::attach_kprobe_multi_with_opts`], always uses BPF links and
+/// never falls back.
+#[non_exhaustive]
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProbeAttachMode {
+    /// Let libbpf pick the mechanism, falling back as necessary.
+    #[default]
+    Default = libbpf_sys::PROBE_ATTACH_MODE_DEFAULT,
+    /// Create the probe via tracefs and attach to the resulting perf event
+    /// via the `PERF_EVENT_IOC_SET_BPF` ioctl.
+    Legacy = libbpf_sys::PROBE_ATTACH_MODE_LEGACY,
+    /// Create the probe via the kprobe/uprobe PMU and attach to the
+    /// resulting perf event via the `PERF_EVENT_IOC_SET_BPF` ioctl.
+    Perf = libbpf_sys::PROBE_ATTACH_MODE_PERF,
+    /// Create the probe via the kprobe/uprobe PMU and attach to the
+    /// resulting perf event via a BPF link.
+    Link = libbpf_sys::PROBE_ATTACH_MODE_LINK,
+}
+
+impl ProbeAttachMode {
+    /// Describe the mode for the purpose of error reporting.
+    fn describe(&self) -> &'static str {
+        match self {
+            Self::Default => "attach mode chosen by libbpf",
+            Self::Legacy => "legacy attach mode, without fallback",
+            Self::Perf => "perf attach mode, without fallback",
+            Self::Link => "link attach mode, without fallback",
+        }
+    }
+}
 
 /// Options to optionally be provided when attaching to a uprobe.
+///
+/// ```no_run
+/// # use libbpf_rs::UprobeOpts;
+/// let opts = UprobeOpts::default().cookie(7).retprobe(true);
+/// ```
 #[derive(Clone, Debug, Default)]
 pub struct UprobeOpts {
     /// Offset of kernel reference counted USDT semaphore.
@@ -39,12 +125,792 @@ pub struct UprobeOpts {
     /// argument to should be 0. To trace an offset within a function, specify
     /// `func_name` and use `func_offset` argument to specify offset within the
     /// function. Shared library functions must specify the shared library
-    /// binary_path.
+    /// binary_path. If empty, `func_offset` is interpreted as a file offset
+    /// within the binary.
     pub func_name: String,
+    /// The mechanism used for attaching.
+    pub attach_mode: ProbeAttachMode,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl UprobeOpts {
+    /// Set [`Self::ref_ctr_offset`].
+    pub fn ref_ctr_offset(mut self, ref_ctr_offset: usize) -> Self {
+        self.ref_ctr_offset = ref_ctr_offset;
+        self
+    }
+
+    /// Set [`Self::cookie`].
+    pub fn cookie(mut self, cookie: u64) -> Self {
+        self.cookie = cookie;
+        self
+    }
+
+    /// Set [`Self::retprobe`].
+    pub fn retprobe(mut self, retprobe: bool) -> Self {
+        self.retprobe = retprobe;
+        self
+    }
+
+    /// Set [`Self::func_name`].
+    pub fn func_name(mut self, func_name: impl Into<String>) -> Self {
+        self.func_name = func_name.into();
+        self
+    }
+
+    /// Set [`Self::attach_mode`].
+    pub fn attach_mode(mut self, attach_mode: ProbeAttachMode) -> Self {
+        self.attach_mode = attach_mode;
+        self
+    }
+
+    /// Create options for attaching a uprobe to the site of the USDT probe
+    /// `provider:name` in `binary_path`.
+    ///
+    /// The probe is looked up in the binary's `.note.stapsdt` section and
+    /// [`Self::ref_ctr_offset`] is set to the file offset of its semaphore,
+    /// if any, so that the kernel takes care of enabling the probe. The
+    /// returned `usize` is the file offset of the probe's site, to be
+    /// passed as `func_offset` to [`Program::attach_uprobe_with_opts`]. It
+    /// is returned alongside the options because they have no field for
+    /// it: [`Self::func_name`] can't describe a USDT site, which is not a
+    /// symbol, so it is left empty, making `func_offset` relative to the
+    /// binary. If the probe has multiple sites, the first one is used.
+    ///
+    /// An error of kind [`ErrorKind::NotFound`][crate::ErrorKind::NotFound]
+    /// is reported if the binary contains no USDT notes or the probe does
+    /// not exist.
+    ///
+    /// Note that, unlike [`Program::attach_usdt`], no USDT arguments are
+    /// made available to the BPF program.
+    pub fn from_usdt(
+        binary_path: impl AsRef<Path>,
+        provider: impl AsRef<str>,
+        name: impl AsRef<str>,
+    ) -> Result<(Self, usize)> {
+        let elf::UsdtNote {
+            pc_offset,
+            sema_offset,
+        } = elf::find_usdt_note(binary_path.as_ref(), provider.as_ref(), name.as_ref())?;
+        let opts = Self {
+            ref_ctr_offset: sema_offset,
+            ..Default::default()
+        };
+        Ok((opts, pc_offset))
+    }
+}
+
+/// Options to optionally be provided when attaching to a kprobe.
+#[derive(Clone, Debug, Default)]
+pub struct KprobeOpts {
+    /// Custom user-provided value accessible through `bpf_get_attach_cookie`.
+    pub cookie: u64,
+    /// Offset within the function to attach to.
+    pub offset: usize,
+    /// The mechanism used for attaching.
+    pub attach_mode: ProbeAttachMode,
     #[doc(hidden)]
     pub _non_exhaustive: (),
 }
 
+impl KprobeOpts {
+    /// Set [`Self::cookie`].
+    pub fn cookie(mut self, cookie: u64) -> Self {
+        self.cookie = cookie;
+        self
+    }
+
+    /// Set [`Self::offset`].
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set [`Self::attach_mode`].
+    pub fn attach_mode(mut self, attach_mode: ProbeAttachMode) -> Self {
+        self.attach_mode = attach_mode;
+        self
+    }
+}
+
+/// Options to optionally be provided when attaching to a ksyscall.
+#[derive(Clone, Debug, Default)]
+pub struct KsyscallOpts {
+    /// Custom user-provided value accessible through `bpf_get_attach_cookie`.
+    pub cookie: u64,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl From<KprobeOpts> for libbpf_sys::bpf_kprobe_opts {
+    fn from(opts: KprobeOpts) -> Self {
+        let KprobeOpts {
+            cookie,
+            offset,
+            attach_mode,
+            _non_exhaustive,
+        } = opts;
+
+        libbpf_sys::bpf_kprobe_opts {
+            sz: size_of::<Self>() as _,
+            bpf_cookie: cookie,
+            offset: offset as libbpf_sys::size_t,
+            attach_mode: attach_mode as libbpf_sys::probe_attach_mode,
+            ..Default::default()
+        }
+    }
+}
+
+impl KsyscallOpts {
+    /// Set [`Self::cookie`].
+    pub fn cookie(mut self, cookie: u64) -> Self {
+        self.cookie = cookie;
+        self
+    }
+}
+
+impl From<KsyscallOpts> for libbpf_sys::bpf_ksyscall_opts {
+    fn from(opts: KsyscallOpts) -> Self {
+        let KsyscallOpts {
+            cookie,
+            _non_exhaustive,
+        } = opts;
+
+        libbpf_sys::bpf_ksyscall_opts {
+            sz: size_of::<Self>() as _,
+            bpf_cookie: cookie,
+            ..Default::default()
+        }
+    }
+}
+
+/// Options to optionally be provided when attaching to a perf event.
+#[derive(Clone, Debug, Default)]
+pub struct PerfEventOpts {
+    /// Custom user-provided value accessible through `bpf_get_attach_cookie`.
+    pub cookie: u64,
+    /// Don't use BPF link when attaching the BPF program, but attach it via
+    /// the legacy `PERF_EVENT_IOC_SET_BPF` ioctl instead.
+    pub force_ioctl_attach: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl PerfEventOpts {
+    /// Set [`Self::cookie`].
+    pub fn cookie(mut self, cookie: u64) -> Self {
+        self.cookie = cookie;
+        self
+    }
+
+    /// Set [`Self::force_ioctl_attach`].
+    pub fn force_ioctl_attach(mut self, force_ioctl_attach: bool) -> Self {
+        self.force_ioctl_attach = force_ioctl_attach;
+        self
+    }
+}
+
+impl From<PerfEventOpts> for libbpf_sys::bpf_perf_event_opts {
+    fn from(opts: PerfEventOpts) -> Self {
+        let PerfEventOpts {
+            cookie,
+            force_ioctl_attach,
+            _non_exhaustive,
+        } = opts;
+
+        libbpf_sys::bpf_perf_event_opts {
+            sz: size_of::<Self>() as _,
+            bpf_cookie: cookie,
+            force_ioctl_attach,
+            ..Default::default()
+        }
+    }
+}
+
+/// How often a perf event invokes the [`Program`] attached to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerfEventSampling {
+    /// Invoke the program once every given number of events.
+    Period(u64),
+    /// Invoke the program the given number of times per second, with the
+    /// kernel adjusting the period accordingly.
+    Frequency(u64),
+}
+
+/// Description of a perf event to open and attach a [`Program`] to, via
+/// [`Program::attach_perf_event_config`].
+#[derive(Clone, Debug)]
+pub struct PerfEventConfig {
+    /// The type of the event, e.g., [`libbpf_sys::PERF_TYPE_SOFTWARE`] or
+    /// [`libbpf_sys::PERF_TYPE_HARDWARE`].
+    pub type_: u32,
+    /// The type specific event configuration, e.g.,
+    /// [`libbpf_sys::PERF_COUNT_SW_CPU_CLOCK`] or
+    /// [`libbpf_sys::PERF_COUNT_HW_CPU_CYCLES`].
+    pub config: u64,
+    /// How often the program is invoked. Events that are not sampled
+    /// periodically, such as tracepoints, typically use a period of `1`, to
+    /// invoke the program on every event.
+    pub sampling: PerfEventSampling,
+    /// The CPU to monitor or `-1` to monitor all CPUs.
+    pub cpu: i32,
+    /// The process to monitor, `0` for the calling process, or `-1` to
+    /// monitor all processes (in which case `cpu` can't be `-1`).
+    pub pid: i32,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for PerfEventConfig {
+    /// Create a configuration sampling the `cpu-clock` software event of
+    /// the calling process on any CPU, at 99 Hz.
+    fn default() -> Self {
+        Self {
+            type_: libbpf_sys::PERF_TYPE_SOFTWARE,
+            config: libbpf_sys::PERF_COUNT_SW_CPU_CLOCK as _,
+            sampling: PerfEventSampling::Frequency(99),
+            cpu: -1,
+            pid: 0,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl PerfEventConfig {
+    /// Set [`Self::type_`].
+    pub fn type_(mut self, type_: u32) -> Self {
+        self.type_ = type_;
+        self
+    }
+
+    /// Set [`Self::config`].
+    pub fn config(mut self, config: u64) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set [`Self::sampling`].
+    pub fn sampling(mut self, sampling: PerfEventSampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Set [`Self::cpu`].
+    pub fn cpu(mut self, cpu: i32) -> Self {
+        self.cpu = cpu;
+        self
+    }
+
+    /// Set [`Self::pid`].
+    pub fn pid(mut self, pid: i32) -> Self {
+        self.pid = pid;
+        self
+    }
+
+    /// Open the described perf event.
+    fn open(&self) -> Result<OwnedFd> {
+        let Self {
+            type_,
+            config,
+            sampling,
+            cpu,
+            pid,
+            _non_exhaustive,
+        } = *self;
+
+        let mut attr = libbpf_sys::perf_event_attr {
+            type_,
+            size: size_of::<libbpf_sys::perf_event_attr>() as _,
+            config,
+            ..Default::default()
+        };
+        match sampling {
+            PerfEventSampling::Period(period) => attr.__bindgen_anon_1.sample_period = period,
+            PerfEventSampling::Frequency(freq) => {
+                attr.__bindgen_anon_1.sample_freq = freq;
+                attr.set_freq(1);
+            }
+        }
+
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const libbpf_sys::perf_event_attr,
+                pid,
+                cpu,
+                -1,
+                libbpf_sys::PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(Error::from(io::Error::last_os_error()));
+        }
+
+        let fd = unsafe {
+            // SAFETY
+            // A file descriptor coming from the perf_event_open system call is always suitable for
+            // ownership and can be cleaned up with close.
+            OwnedFd::from_raw_fd(fd as _)
+        };
+        Ok(fd)
+    }
+}
+
+bitflags! {
+    /// Flags to configure the attachment of a [`Program`] to a cgroup.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+    pub struct CgroupAttachFlags: u32 {
+        /// See [`libbpf_sys::BPF_F_ALLOW_OVERRIDE`].
+        const ALLOW_OVERRIDE = libbpf_sys::BPF_F_ALLOW_OVERRIDE as _;
+        /// See [`libbpf_sys::BPF_F_ALLOW_MULTI`].
+        const ALLOW_MULTI    = libbpf_sys::BPF_F_ALLOW_MULTI as _;
+    }
+}
+
+/// Options to optionally be provided when attaching to a cgroup.
+#[derive(Clone, Debug, Default)]
+pub struct CgroupAttachOpts {
+    /// Flags controlling how the program co-exists with other programs
+    /// attached to the same cgroup hook.
+    pub flags: CgroupAttachFlags,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl CgroupAttachOpts {
+    /// Set [`Self::flags`].
+    pub fn flags(mut self, flags: CgroupAttachFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl From<CgroupAttachOpts> for libbpf_sys::bpf_prog_attach_opts {
+    fn from(opts: CgroupAttachOpts) -> Self {
+        let CgroupAttachOpts {
+            flags,
+            _non_exhaustive,
+        } = opts;
+
+        libbpf_sys::bpf_prog_attach_opts {
+            sz: size_of::<Self>() as _,
+            flags: flags.bits(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options to be provided when attaching to a netfilter hook.
+#[derive(Clone, Debug, Default)]
+pub struct NetfilterOpts {
+    /// The protocol family of the hook, e.g., [`libc::NFPROTO_IPV4`] or
+    /// [`libc::NFPROTO_IPV6`].
+    pub pf: u32,
+    /// The hook to attach to, e.g., [`libc::NF_INET_LOCAL_IN`].
+    pub hooknum: u32,
+    /// The priority of the program relative to other netfilter hooks.
+    ///
+    /// Netfilter hooks are run in order of ascending priority. Unlike with
+    /// TCX (see [`TcxOpts::before`]), the kernel provides no means of
+    /// positioning a program relative to a specific other one.
+    pub priority: i32,
+    /// Flags for the attachment, e.g.,
+    /// [`libbpf_sys::BPF_F_NETFILTER_IP_DEFRAG`].
+    pub flags: u32,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl NetfilterOpts {
+    /// Set [`Self::pf`].
+    pub fn pf(mut self, pf: u32) -> Self {
+        self.pf = pf;
+        self
+    }
+
+    /// Set [`Self::hooknum`].
+    pub fn hooknum(mut self, hooknum: u32) -> Self {
+        self.hooknum = hooknum;
+        self
+    }
+
+    /// Set [`Self::priority`].
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set [`Self::flags`].
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl From<NetfilterOpts> for libbpf_sys::bpf_netfilter_opts {
+    fn from(opts: NetfilterOpts) -> Self {
+        let NetfilterOpts {
+            pf,
+            hooknum,
+            priority,
+            flags,
+            _non_exhaustive,
+        } = opts;
+
+        libbpf_sys::bpf_netfilter_opts {
+            sz: size_of::<Self>() as _,
+            pf,
+            hooknum,
+            priority,
+            flags,
+        }
+    }
+}
+
+/// The attach point of a TCX program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TcxAttachPoint {
+    /// Ingress traffic, i.e., [`ProgramAttachType::TcxIngress`].
+    #[default]
+    Ingress,
+    /// Egress traffic, i.e., [`ProgramAttachType::TcxEgress`].
+    Egress,
+}
+
+/// Options to be provided when attaching to a TCX hook.
+#[derive(Clone, Debug, Default)]
+pub struct TcxOpts {
+    /// The direction of traffic the program should be invoked for.
+    pub attach_point: TcxAttachPoint,
+    /// Flags for the attachment, e.g., [`libbpf_sys::BPF_F_BEFORE`] or
+    /// [`libbpf_sys::BPF_F_AFTER`].
+    pub flags: u32,
+    /// File descriptor of a program or link the position of the new program
+    /// is relative to, if any.
+    pub relative_fd: i32,
+    /// ID of a program or link the position of the new program is relative
+    /// to, if any.
+    pub relative_id: u32,
+    /// The revision of the hook's program list the attachment is expected
+    /// to happen against, or `0` to not perform the check.
+    pub expected_revision: u64,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl TcxOpts {
+    /// Set [`Self::attach_point`].
+    pub fn attach_point(mut self, attach_point: TcxAttachPoint) -> Self {
+        self.attach_point = attach_point;
+        self
+    }
+
+    /// Set [`Self::flags`].
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set [`Self::relative_fd`].
+    pub fn relative_fd(mut self, relative_fd: i32) -> Self {
+        self.relative_fd = relative_fd;
+        self
+    }
+
+    /// Set [`Self::relative_id`].
+    pub fn relative_id(mut self, relative_id: u32) -> Self {
+        self.relative_id = relative_id;
+        self
+    }
+
+    /// Set [`Self::expected_revision`].
+    pub fn expected_revision(mut self, expected_revision: u64) -> Self {
+        self.expected_revision = expected_revision;
+        self
+    }
+
+    /// Position the new program directly before the one attached via
+    /// `link`, by setting [`Self::relative_id`] to the link's ID along with
+    /// the [`libbpf_sys::BPF_F_BEFORE`], [`libbpf_sys::BPF_F_LINK`], and
+    /// [`libbpf_sys::BPF_F_ID`] flags.
+    ///
+    /// If `link` is gone by the time the program is attached, attaching
+    /// fails.
+    pub fn before(self, link: &Link) -> Result<Self> {
+        self.relative_to_link(link, libbpf_sys::BPF_F_BEFORE)
+    }
+
+    /// Position the new program directly after the one attached via
+    /// `link`, by setting [`Self::relative_id`] to the link's ID along with
+    /// the [`libbpf_sys::BPF_F_AFTER`], [`libbpf_sys::BPF_F_LINK`], and
+    /// [`libbpf_sys::BPF_F_ID`] flags.
+    ///
+    /// If `link` is gone by the time the program is attached, attaching
+    /// fails.
+    pub fn after(self, link: &Link) -> Result<Self> {
+        self.relative_to_link(link, libbpf_sys::BPF_F_AFTER)
+    }
+
+    fn relative_to_link(mut self, link: &Link, position: u32) -> Result<Self> {
+        let link_id = link
+            .info()
+            .context("failed to retrieve ID of link to attach relative to")?
+            .id;
+        self.flags &= !(libbpf_sys::BPF_F_BEFORE | libbpf_sys::BPF_F_AFTER);
+        self.flags |= position | libbpf_sys::BPF_F_LINK | libbpf_sys::BPF_F_ID;
+        self.relative_fd = 0;
+        self.relative_id = link_id;
+        Ok(self)
+    }
+}
+
+/// The programs attached to a TCX hook, in the order they are run, as
+/// reported by [`Program::query_tcx`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TcxChain {
+    /// The IDs of the attached programs.
+    pub prog_ids: Vec<u32>,
+    /// The IDs of the links the programs are attached through, with `0`
+    /// for programs not attached through a link. Matches
+    /// [`Self::prog_ids`] element by element.
+    pub link_ids: Vec<u32>,
+    /// The revision of the hook's program list, changing with every
+    /// attachment and detachment. Suitable for
+    /// [`TcxOpts::expected_revision`].
+    pub revision: u64,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Options to be provided when attaching to XDP.
+#[derive(Clone, Debug, Default)]
+pub struct XdpOpts {
+    /// Flags controlling the attach mode, e.g., [`XdpFlags::SKB_MODE`].
+    pub flags: XdpFlags,
+    /// File descriptor of the currently attached XDP program to atomically
+    /// replace, if any. [`XdpFlags::REPLACE`] is implied if set.
+    pub old_prog_fd: Option<i32>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl XdpOpts {
+    /// Set [`Self::flags`].
+    pub fn flags(mut self, flags: XdpFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set [`Self::old_prog_fd`].
+    pub fn old_prog_fd(mut self, old_prog_fd: i32) -> Self {
+        self.old_prog_fd = Some(old_prog_fd);
+        self
+    }
+}
+
+/// The order in which a cgroup iterator walks the cgroup hierarchy.
+#[non_exhaustive]
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CgroupIterOrder {
+    /// Visit only the provided cgroup.
+    #[default]
+    SelfOnly = libbpf_sys::BPF_CGROUP_ITER_SELF_ONLY,
+    /// Walk the descendants of the provided cgroup in pre-order.
+    DescendantsPre = libbpf_sys::BPF_CGROUP_ITER_DESCENDANTS_PRE,
+    /// Walk the descendants of the provided cgroup in post-order.
+    DescendantsPost = libbpf_sys::BPF_CGROUP_ITER_DESCENDANTS_POST,
+    /// Walk the ancestors of the provided cgroup upwards.
+    AncestorsUp = libbpf_sys::BPF_CGROUP_ITER_ANCESTORS_UP,
+}
+
+/// The kernel objects a
+/// [BPF Iterator](https://www.kernel.org/doc/html/latest/bpf/bpf_iterators.html)
+/// should be restricted to.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug)]
+pub enum IterOpts<'fd> {
+    /// Iterate over the elements of the provided map. Used by, e.g.,
+    /// `SEC("iter/bpf_map_elem")` programs.
+    Map(BorrowedFd<'fd>),
+    /// Iterate over cgroups, starting at the one referred to by the provided
+    /// file descriptor. Used by `SEC("iter/cgroup")` programs.
+    Cgroup {
+        /// File descriptor of the cgroup to start at.
+        fd: BorrowedFd<'fd>,
+        /// The order in which to walk the cgroup hierarchy.
+        order: CgroupIterOrder,
+    },
+    /// Iterate over cgroups, starting at the one with the provided ID. Used
+    /// by `SEC("iter/cgroup")` programs.
+    CgroupId {
+        /// ID of the cgroup to start at.
+        id: u64,
+        /// The order in which to walk the cgroup hierarchy.
+        order: CgroupIterOrder,
+    },
+    /// Only iterate over the task with the provided thread ID. Used by
+    /// `SEC("iter/task")`, `SEC("iter/task_file")`, and `SEC("iter/task_vma")`
+    /// programs.
+    Tid(u32),
+    /// Only iterate over the tasks of the process with the provided ID.
+    Pid(u32),
+    /// Only iterate over the tasks of the process referred to by the provided
+    /// pidfd.
+    PidFd(BorrowedFd<'fd>),
+}
+
+impl From<IterOpts<'_>> for libbpf_sys::bpf_iter_link_info {
+    fn from(opts: IterOpts<'_>) -> Self {
+        let mut linkinfo = libbpf_sys::bpf_iter_link_info::default();
+        match opts {
+            IterOpts::Map(fd) => {
+                linkinfo.map.map_fd = fd.as_raw_fd() as _;
+            }
+            IterOpts::Cgroup { fd, order } => {
+                linkinfo.cgroup.cgroup_fd = fd.as_raw_fd() as _;
+                linkinfo.cgroup.order = order as _;
+            }
+            IterOpts::CgroupId { id, order } => {
+                linkinfo.cgroup.cgroup_id = id;
+                linkinfo.cgroup.order = order as _;
+            }
+            IterOpts::Tid(tid) => {
+                linkinfo.task.tid = tid;
+            }
+            IterOpts::Pid(pid) => {
+                linkinfo.task.pid = pid;
+            }
+            IterOpts::PidFd(fd) => {
+                linkinfo.task.pid_fd = fd.as_raw_fd() as _;
+            }
+        }
+        linkinfo
+    }
+}
+
+/// Options to optionally be provided when attaching to multiple uprobes.
+///
+/// Exactly one of `symbols`, `offsets`, or `pattern` should be provided.
+#[derive(Clone, Debug, Default)]
+pub struct UprobeMultiOpts {
+    /// List of function symbol names to attach to.
+    pub symbols: Vec<String>,
+    /// List of function offsets within the binary to attach to.
+    pub offsets: Vec<usize>,
+    /// Glob pattern (e.g., "malloc*") matching the functions to attach to.
+    pub pattern: Option<String>,
+    /// Offsets of kernel reference counted USDT semaphores.
+    ///
+    /// If not empty, there has to be one entry per entry in `symbols` or
+    /// `offsets`.
+    pub ref_ctr_offsets: Vec<usize>,
+    /// Custom user-provided values accessible through `bpf_get_attach_cookie`.
+    ///
+    /// If not empty, there has to be one cookie per entry in `symbols` or
+    /// `offsets`.
+    pub cookies: Vec<u64>,
+    /// uprobes are return probes, invoked at function return time.
+    pub retprobe: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl UprobeMultiOpts {
+    /// Set [`Self::symbols`].
+    pub fn symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Set [`Self::offsets`].
+    pub fn offsets(mut self, offsets: Vec<usize>) -> Self {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Set [`Self::pattern`].
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Set [`Self::ref_ctr_offsets`].
+    pub fn ref_ctr_offsets(mut self, ref_ctr_offsets: Vec<usize>) -> Self {
+        self.ref_ctr_offsets = ref_ctr_offsets;
+        self
+    }
+
+    /// Set [`Self::cookies`].
+    pub fn cookies(mut self, cookies: Vec<u64>) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    /// Set [`Self::retprobe`].
+    pub fn retprobe(mut self, retprobe: bool) -> Self {
+        self.retprobe = retprobe;
+        self
+    }
+}
+
+/// Options to optionally be provided when attaching to multiple kprobes.
+///
+/// Exactly one of `symbols`, `addresses`, or `pattern` should be provided.
+#[derive(Clone, Debug, Default)]
+pub struct KprobeMultiOpts {
+    /// List of kernel symbol names to attach to.
+    pub symbols: Vec<String>,
+    /// List of kernel function addresses to attach to.
+    pub addresses: Vec<usize>,
+    /// Glob pattern (e.g., "tcp_*") matching the kernel functions to attach
+    /// to.
+    pub pattern: Option<String>,
+    /// Custom user-provided values accessible through `bpf_get_attach_cookie`.
+    ///
+    /// If not empty, there has to be one cookie per entry in `symbols` or
+    /// `addresses`.
+    pub cookies: Vec<u64>,
+    /// kprobes are return probes, invoked at function return time.
+    pub retprobe: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl KprobeMultiOpts {
+    /// Set [`Self::symbols`].
+    pub fn symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Set [`Self::addresses`].
+    pub fn addresses(mut self, addresses: Vec<usize>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Set [`Self::pattern`].
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Set [`Self::cookies`].
+    pub fn cookies(mut self, cookies: Vec<u64>) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    /// Set [`Self::retprobe`].
+    pub fn retprobe(mut self, retprobe: bool) -> Self {
+        self.retprobe = retprobe;
+        self
+    }
+}
+
 /// Options to optionally be provided when attaching to a USDT.
 #[derive(Clone, Debug, Default)]
 pub struct UsdtOpts {
@@ -54,6 +920,14 @@ pub struct UsdtOpts {
     pub _non_exhaustive: (),
 }
 
+impl UsdtOpts {
+    /// Set [`Self::cookie`].
+    pub fn cookie(mut self, cookie: u64) -> Self {
+        self.cookie = cookie;
+        self
+    }
+}
+
 impl From<UsdtOpts> for libbpf_sys::bpf_usdt_opts {
     fn from(opts: UsdtOpts) -> Self {
         let UsdtOpts {
@@ -76,6 +950,14 @@ pub struct TracepointOpts {
     pub _non_exhaustive: (),
 }
 
+impl TracepointOpts {
+    /// Set [`Self::cookie`].
+    pub fn cookie(mut self, cookie: u64) -> Self {
+        self.cookie = cookie;
+        self
+    }
+}
+
 impl From<TracepointOpts> for libbpf_sys::bpf_tracepoint_opts {
     fn from(opts: TracepointOpts) -> Self {
         let TracepointOpts {
@@ -90,6 +972,38 @@ impl From<TracepointOpts> for libbpf_sys::bpf_tracepoint_opts {
     }
 }
 
+/// Options to optionally be provided when attaching to a fentry/fexit
+/// program.
+#[derive(Clone, Debug, Default)]
+pub struct TraceOpts {
+    /// Custom user-provided value accessible through `bpf_get_attach_cookie`.
+    pub cookie: u64,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl TraceOpts {
+    /// Set [`Self::cookie`].
+    pub fn cookie(mut self, cookie: u64) -> Self {
+        self.cookie = cookie;
+        self
+    }
+}
+
+impl From<TraceOpts> for libbpf_sys::bpf_trace_opts {
+    fn from(opts: TraceOpts) -> Self {
+        let TraceOpts {
+            cookie,
+            _non_exhaustive,
+        } = opts;
+
+        libbpf_sys::bpf_trace_opts {
+            sz: size_of::<Self>() as _,
+            cookie,
+        }
+    }
+}
+
 /// Represents a parsed but not yet loaded BPF program.
 ///
 /// This object exposes operations that need to happen before the program is loaded.
@@ -97,6 +1011,8 @@ impl From<TracepointOpts> for libbpf_sys::bpf_tracepoint_opts {
 pub struct OpenProgram {
     ptr: NonNull<libbpf_sys::bpf_program>,
     section: String,
+    ifindex: u32,
+    pub(crate) log_buf: Option<Box<[c_char]>>,
 }
 
 // TODO: Document variants.
@@ -109,7 +1025,12 @@ impl OpenProgram {
         let section = unsafe { libbpf_sys::bpf_program__section_name(ptr.as_ptr()) };
         let section = util::c_ptr_to_string(section)?;
 
-        Ok(Self { ptr, section })
+        Ok(Self {
+            ptr,
+            section,
+            ifindex: 0,
+            log_buf: None,
+        })
     }
 
     pub fn set_prog_type(&mut self, prog_type: ProgramType) {
@@ -135,10 +1056,28 @@ impl OpenProgram {
         }
     }
 
+    /// Retrieve the expected attach type of the program.
+    pub fn attach_type(&self) -> ProgramAttachType {
+        match ProgramAttachType::try_from(unsafe {
+            libbpf_sys::bpf_program__expected_attach_type(self.ptr.as_ptr())
+        }) {
+            Ok(ty) => ty,
+            Err(_) => ProgramAttachType::Unknown,
+        }
+    }
+
     pub fn set_ifindex(&mut self, idx: u32) {
         unsafe {
             libbpf_sys::bpf_program__set_ifindex(self.ptr.as_ptr(), idx);
         }
+        self.ifindex = idx;
+    }
+
+    /// Retrieve the interface index the program is to be offloaded to, as
+    /// set by [`OpenProgram::set_ifindex`]. `0` means no offloading.
+    pub fn ifindex(&self) -> u32 {
+        // libbpf does not provide a getter, so we report what we set.
+        self.ifindex
     }
 
     /// Set the log level for the bpf program.
@@ -154,6 +1093,35 @@ impl OpenProgram {
         util::parse_ret(ret)
     }
 
+    /// Retrieve the log level for the bpf program, as set via
+    /// [`OpenProgram::set_log_level`].
+    pub fn log_level(&self) -> u32 {
+        unsafe { libbpf_sys::bpf_program__log_level(self.ptr.as_ptr()) }
+    }
+
+    /// Provide a buffer of `size` bytes for the kernel to write the program's
+    /// verifier log into, replacing any previously set one. A `size` of zero
+    /// removes the buffer.
+    ///
+    /// The log can be retrieved via [`Program::log_buf`] once the object got
+    /// loaded. Unless a log level is set via [`OpenProgram::set_log_level`], the
+    /// kernel only writes to the buffer if the program fails to load.
+    /// If the log does not fit into the buffer, loading fails with `ENOSPC`.
+    pub fn set_log_buf(&mut self, size: usize) -> Result<()> {
+        let mut log_buf = (size > 0).then(|| vec![0; size].into_boxed_slice());
+        let log_buf_ptr = log_buf
+            .as_mut()
+            .map(|buf| buf.as_mut_ptr())
+            .unwrap_or_else(ptr::null_mut);
+        let ret = unsafe {
+            libbpf_sys::bpf_program__set_log_buf(self.ptr.as_ptr(), log_buf_ptr, size as _)
+        };
+        let () = util::parse_ret(ret)?;
+        // libbpf no longer refers to any previously set buffer.
+        self.log_buf = log_buf;
+        Ok(())
+    }
+
     /// Name of the section this `OpenProgram` belongs to.
     pub fn section(&self) -> &str {
         &self.section
@@ -173,10 +1141,131 @@ impl OpenProgram {
         util::parse_ret(ret)
     }
 
+    /// Set whether a bpf program should be automatically attached when
+    /// attaching a skeleton, e.g., via [`Skel::attach`][crate::skel::Skel::attach].
+    pub fn set_autoattach(&mut self, autoattach: bool) {
+        unsafe { libbpf_sys::bpf_program__set_autoattach(self.ptr.as_ptr(), autoattach) };
+    }
+
+    /// Return `true` if the bpf program is set to be automatically attached,
+    /// `false` otherwise.
+    pub fn autoattach(&self) -> bool {
+        unsafe { libbpf_sys::bpf_program__autoattach(self.ptr.as_ptr()) }
+    }
+
+    /// Set the target the program is to be attached to, for programs that
+    /// are verified against a specific function, e.g., fentry/fexit and
+    /// extension programs.
+    ///
+    /// With `attach_prog_fd` set, `attach_func_name` refers to a function of
+    /// that already loaded BPF program. With `None`, `attach_func_name`
+    /// refers to a kernel function, which is looked up in vmlinux and kernel
+    /// module BTF.
+    ///
+    /// libbpf uses the file descriptor again when the program is loaded, so
+    /// it has to stay open until then. The borrow is not tied to the open
+    /// object, so this is *not* enforced by the compiler; consider
+    /// [`OpenProgram::set_attach_target_program`] instead.
+    pub fn set_attach_target_fd(
+        &mut self,
+        attach_prog_fd: Option<BorrowedFd<'_>>,
+        attach_func_name: Option<String>,
+    ) -> Result<()> {
+        let attach_prog_fd = attach_prog_fd.map_or(0, |fd| fd.as_raw_fd());
+        self.set_attach_target_impl(attach_prog_fd, attach_func_name)
+    }
+
+    /// Set the target the program is to be attached to, identifying the
+    /// target program by a raw file descriptor, with `0` meaning none.
+    #[deprecated(note = "use `OpenProgram::set_attach_target_fd` with a `BorrowedFd` instead")]
     pub fn set_attach_target(
         &mut self,
         attach_prog_fd: i32,
         attach_func_name: Option<String>,
+    ) -> Result<()> {
+        self.set_attach_target_impl(attach_prog_fd, attach_func_name)
+    }
+
+    /// Set the target the program is to be attached to to the kernel
+    /// function `func_name`.
+    ///
+    /// The function is looked up in vmlinux BTF first and, failing that, in
+    /// the BTF of loaded kernel modules. This is equivalent to
+    /// `set_attach_target_fd(None, Some(func_name))`.
+    pub fn set_attach_target_kernel(&mut self, func_name: &str) -> Result<()> {
+        self.set_attach_target_impl(0, Some(func_name.to_string()))
+    }
+
+    /// Set the target the program is to be attached to to the function
+    /// `func_name` of the kernel module `module_name`.
+    ///
+    /// It is checked that the module is loaded and that its BTF contains the
+    /// function. Note that the function is resolved by name, looking at
+    /// vmlinux BTF before that of modules, so a function of the same name in
+    /// vmlinux takes precedence.
+    ///
+    /// Kernel module BTF is available since Linux 5.11, with
+    /// `CONFIG_DEBUG_INFO_BTF_MODULES` enabled. Attaching to module functions
+    /// requires the same kernel version.
+    pub fn set_attach_target_module(&mut self, module_name: &str, func_name: &str) -> Result<()> {
+        let info = BtfInfoIter::default()
+            .find(|info| info.name.as_bytes() == module_name.as_bytes())
+            .ok_or_else(|| {
+                Error::with_io_error(
+                    io::ErrorKind::NotFound,
+                    format!("no BTF found for kernel module `{module_name}`"),
+                )
+            })?;
+        let vmlinux = Btf::from_vmlinux()?;
+        let btf = Btf::from_id_split(info.id, &vmlinux)?;
+        if btf.type_by_name::<Func<'_>>(func_name).is_none() {
+            return Err(Error::with_io_error(
+                io::ErrorKind::NotFound,
+                format!("function `{func_name}` not found in kernel module `{module_name}`"),
+            ));
+        }
+
+        self.set_attach_target_impl(0, Some(func_name.to_string()))
+    }
+
+    /// Set the target the program is to be attached to to the function
+    /// `func_name` of the already loaded BPF program `target`, e.g., to
+    /// instrument it with fentry/fexit programs.
+    ///
+    /// It is checked that `target` carries BTF and that its BTF contains the
+    /// function, which may be the program's main function or any of its
+    /// global subprograms. `target` has to stay loaded until this program is
+    /// loaded. Once loaded, attach the program via
+    /// [`Program::attach_trace`], as usual.
+    ///
+    /// The target cannot be changed after loading: the kernel verifies the
+    /// program against the target's function.
+    pub fn set_attach_target_program(&mut self, target: &Program, func_name: &str) -> Result<()> {
+        let btf_id = target.info()?.btf_id;
+        if btf_id == 0 {
+            return Err(Error::with_invalid_data(format!(
+                "target program `{}` has no BTF",
+                target.name()
+            )));
+        }
+        let btf = Btf::from_id(btf_id)?;
+        if btf.type_by_name::<Func<'_>>(func_name).is_none() {
+            return Err(Error::with_io_error(
+                io::ErrorKind::NotFound,
+                format!(
+                    "function `{func_name}` not found in program `{}`",
+                    target.name()
+                ),
+            ));
+        }
+
+        self.set_attach_target_impl(target.as_fd().as_raw_fd(), Some(func_name.to_string()))
+    }
+
+    fn set_attach_target_impl(
+        &mut self,
+        attach_prog_fd: i32,
+        attach_func_name: Option<String>,
     ) -> Result<()> {
         let ret = if let Some(name) = attach_func_name {
             // NB: we must hold onto a CString otherwise our pointer dangles
@@ -200,11 +1289,24 @@ impl OpenProgram {
         util::parse_ret(ret)
     }
 
+    /// Set the flags the program is loaded with, e.g.,
+    /// [`libbpf_sys::BPF_F_SLEEPABLE`] or [`libbpf_sys::BPF_F_XDP_HAS_FRAGS`].
+    ///
+    /// The latter declares an XDP program as supporting multi-buffer
+    /// packets, e.g., jumbo frames on devices with an MTU larger than a
+    /// page, which is required for attaching the program to such devices
+    /// (see [`Program::attach_xdp_frags`]). libbpf sets it automatically for
+    /// programs defined with `SEC("xdp.frags")`.
     pub fn set_flags(&self, flags: u32) -> Result<()> {
         let ret = unsafe { libbpf_sys::bpf_program__set_flags(self.ptr.as_ptr(), flags) };
         util::parse_ret(ret)
     }
 
+    /// Returns flags that have been set for the program.
+    pub fn flags(&self) -> u32 {
+        unsafe { libbpf_sys::bpf_program__flags(self.ptr.as_ptr()) }
+    }
+
     /// Returns the number of instructions that form the program.
     ///
     /// Note: Keep in mind, libbpf can modify the program's instructions
@@ -229,6 +1331,48 @@ impl OpenProgram {
         let ptr = unsafe { libbpf_sys::bpf_program__insns(self.ptr.as_ptr()) };
         unsafe { slice::from_raw_parts(ptr, count) }
     }
+
+    /// Disassemble the program's instructions, as returned by
+    /// [`OpenProgram::insns`], into text, one line per instruction.
+    ///
+    /// The output follows the style of `bpftool prog dump xlated`, e.g.,
+    /// `   0: (b7) r0 = 0`.
+    pub fn disassemble(&self) -> String {
+        disasm::disassemble(self.insns())
+    }
+
+    /// Decode the program's instructions, as returned by
+    /// [`OpenProgram::insns`], for programmatic analysis.
+    pub fn decoded_insns(&self) -> impl Iterator<Item = DecodedInsn> + '_ {
+        disasm::decode(self.insns())
+    }
+
+    /// Replace the program's instructions with `insns`.
+    ///
+    /// This has to happen before the object is loaded. The instructions are
+    /// copied and used as is; libbpf does not apply any relocations to them.
+    pub fn set_insns(&mut self, insns: &[libbpf_sys::bpf_insn]) -> Result<()> {
+        let insn_cnt = libbpf_sys::size_t::try_from(insns.len())
+            .map_err(|_| Error::with_invalid_data("too many instructions"))?;
+        let ret = unsafe {
+            // SAFETY: libbpf copies the instructions and does not modify them.
+            libbpf_sys::bpf_program__set_insns(
+                self.ptr.as_ptr(),
+                insns.as_ptr().cast_mut(),
+                insn_cnt,
+            )
+        };
+        util::parse_ret(ret)
+    }
+}
+
+impl AsRawLibbpf for OpenProgram {
+    type LibbpfType = libbpf_sys::bpf_program;
+
+    /// Retrieve the underlying [`libbpf_sys::bpf_program`].
+    fn as_libbpf_object(&self) -> NonNull<Self::LibbpfType> {
+        self.ptr
+    }
 }
 
 impl AsRawLibbpf for Program {
@@ -279,11 +1423,264 @@ pub enum ProgramType {
     Lsm,
     SkLookup,
     Syscall,
+    Netfilter,
     /// See [`MapType::Unknown`][crate::MapType::Unknown]
     Unknown = u32::MAX,
 }
 
+/// All known [`ProgramType`] variants, excluding [`ProgramType::Unknown`].
+const PROGRAM_TYPES: [ProgramType; 33] = [
+    ProgramType::Unspec,
+    ProgramType::SocketFilter,
+    ProgramType::Kprobe,
+    ProgramType::SchedCls,
+    ProgramType::SchedAct,
+    ProgramType::Tracepoint,
+    ProgramType::Xdp,
+    ProgramType::PerfEvent,
+    ProgramType::CgroupSkb,
+    ProgramType::CgroupSock,
+    ProgramType::LwtIn,
+    ProgramType::LwtOut,
+    ProgramType::LwtXmit,
+    ProgramType::SockOps,
+    ProgramType::SkSkb,
+    ProgramType::CgroupDevice,
+    ProgramType::SkMsg,
+    ProgramType::RawTracepoint,
+    ProgramType::CgroupSockAddr,
+    ProgramType::LwtSeg6local,
+    ProgramType::LircMode2,
+    ProgramType::SkReuseport,
+    ProgramType::FlowDissector,
+    ProgramType::CgroupSysctl,
+    ProgramType::RawTracepointWritable,
+    ProgramType::CgroupSockopt,
+    ProgramType::Tracing,
+    ProgramType::StructOps,
+    ProgramType::Ext,
+    ProgramType::Lsm,
+    ProgramType::SkLookup,
+    ProgramType::Syscall,
+    ProgramType::Netfilter,
+];
+
+/// Load a trivial program (`r0 = 0; exit`) of the given type, for the
+/// purpose of probing kernel support.
+fn load_probe_prog(
+    prog_type: u32,
+    opts: &mut libbpf_sys::bpf_prog_load_opts,
+) -> io::Result<OwnedFd> {
+    let insns = [
+        libbpf_sys::bpf_insn {
+            code: (libbpf_sys::BPF_ALU64 | libbpf_sys::BPF_MOV | libbpf_sys::BPF_K) as _,
+            ..Default::default()
+        },
+        libbpf_sys::bpf_insn {
+            code: (libbpf_sys::BPF_JMP | libbpf_sys::BPF_EXIT) as _,
+            ..Default::default()
+        },
+    ];
+    let fd = unsafe {
+        libbpf_sys::bpf_prog_load(
+            prog_type,
+            ptr::null(),
+            b"GPL\0".as_ptr().cast(),
+            insns.as_ptr(),
+            insns.len() as _,
+            opts,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::from_raw_os_error(-fd));
+    }
+
+    // SAFETY
+    // A file descriptor coming from the bpf_prog_load function is always suitable for
+    // ownership and can be cleaned up with close.
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
 impl ProgramType {
+    /// The kernel's name for this program type, e.g., `"sched_cls"` for
+    /// [`ProgramType::SchedCls`].
+    ///
+    /// [`ProgramType::Unknown`] is reported as `"unknown"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Unspec => "unspec",
+            Self::SocketFilter => "socket_filter",
+            Self::Kprobe => "kprobe",
+            Self::SchedCls => "sched_cls",
+            Self::SchedAct => "sched_act",
+            Self::Tracepoint => "tracepoint",
+            Self::Xdp => "xdp",
+            Self::PerfEvent => "perf_event",
+            Self::CgroupSkb => "cgroup_skb",
+            Self::CgroupSock => "cgroup_sock",
+            Self::LwtIn => "lwt_in",
+            Self::LwtOut => "lwt_out",
+            Self::LwtXmit => "lwt_xmit",
+            Self::SockOps => "sock_ops",
+            Self::SkSkb => "sk_skb",
+            Self::CgroupDevice => "cgroup_device",
+            Self::SkMsg => "sk_msg",
+            Self::RawTracepoint => "raw_tracepoint",
+            Self::CgroupSockAddr => "cgroup_sock_addr",
+            Self::LwtSeg6local => "lwt_seg6local",
+            Self::LircMode2 => "lirc_mode2",
+            Self::SkReuseport => "sk_reuseport",
+            Self::FlowDissector => "flow_dissector",
+            Self::CgroupSysctl => "cgroup_sysctl",
+            Self::RawTracepointWritable => "raw_tracepoint_writable",
+            Self::CgroupSockopt => "cgroup_sockopt",
+            Self::Tracing => "tracing",
+            Self::StructOps => "struct_ops",
+            Self::Ext => "ext",
+            Self::Lsm => "lsm",
+            Self::SkLookup => "sk_lookup",
+            Self::Syscall => "syscall",
+            Self::Netfilter => "netfilter",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Returns an iterator over all known program types, excluding
+    /// [`ProgramType::Unknown`].
+    ///
+    /// Combined with [`ProgramType::is_supported`], this can be used to
+    /// probe the kernel for all the program types it supports.
+    pub fn iter() -> impl Iterator<Item = ProgramType> {
+        PROGRAM_TYPES.into_iter()
+    }
+
+    /// The size of the context struct programs of this type operate on, as
+    /// provided via [`ProgramInput::context_in`][Input::context_in] when
+    /// test running them, e.g., that of `struct xdp_md` for
+    /// [`Xdp`][Self::Xdp] and of `struct __sk_buff` for
+    /// [`SchedCls`][Self::SchedCls].
+    ///
+    /// [`FlowDissector`][Self::FlowDissector] programs are test run with a
+    /// `struct bpf_flow_keys` instead of their `struct __sk_buff`.
+    ///
+    /// `None` is returned for program types without a context struct, e.g.,
+    /// [`Tracing`][Self::Tracing] or [`Syscall`][Self::Syscall], as well as
+    /// for those whose context struct size is not known, e.g., because it
+    /// is architecture specific.
+    pub fn context_struct_size(&self) -> Option<usize> {
+        let size = match self {
+            Self::SocketFilter
+            | Self::SchedCls
+            | Self::SchedAct
+            | Self::CgroupSkb
+            | Self::LwtIn
+            | Self::LwtOut
+            | Self::LwtXmit
+            | Self::LwtSeg6local
+            | Self::SkSkb => SK_BUFF_SIZE,
+            Self::Xdp => size_of::<libbpf_sys::xdp_md>(),
+            Self::FlowDissector => size_of::<libbpf_sys::bpf_flow_keys>(),
+            Self::CgroupSock => size_of::<libbpf_sys::bpf_sock>(),
+            Self::SockOps => size_of::<libbpf_sys::bpf_sock_ops>(),
+            Self::CgroupDevice => size_of::<libbpf_sys::bpf_cgroup_dev_ctx>(),
+            Self::CgroupSockAddr => size_of::<libbpf_sys::bpf_sock_addr>(),
+            Self::CgroupSysctl => size_of::<libbpf_sys::bpf_sysctl>(),
+            Self::CgroupSockopt => size_of::<libbpf_sys::bpf_sockopt>(),
+            Self::SkLookup => size_of::<libbpf_sys::bpf_sk_lookup>(),
+            _ => return None,
+        };
+        Some(size)
+    }
+
+    /// Check whether this is a tracing program type.
+    ///
+    /// These are the types the kernel's verifier considers tracing programs
+    /// (see `is_tracing_prog_type`), i.e., [`Kprobe`][Self::Kprobe],
+    /// [`Tracepoint`][Self::Tracepoint], [`PerfEvent`][Self::PerfEvent],
+    /// [`RawTracepoint`][Self::RawTracepoint], and
+    /// [`RawTracepointWritable`][Self::RawTracepointWritable], as well as
+    /// the BTF based [`Tracing`][Self::Tracing] programs (`fentry`,
+    /// `fexit`, `tp_btf`, ...).
+    pub fn is_tracing(&self) -> bool {
+        matches!(
+            self,
+            Self::Kprobe
+                | Self::Tracepoint
+                | Self::PerfEvent
+                | Self::RawTracepoint
+                | Self::RawTracepointWritable
+                | Self::Tracing
+        )
+    }
+
+    /// Check whether this is a networking program type, i.e., one
+    /// operating on packets, sockets, or socket messages.
+    ///
+    /// This is based on the program types for which the kernel requires
+    /// `CAP_NET_ADMIN` (see `is_net_admin_prog_type`), minus
+    /// [`CgroupDevice`][Self::CgroupDevice],
+    /// [`CgroupSysctl`][Self::CgroupSysctl], and [`Ext`][Self::Ext], plus
+    /// the socket related [`SocketFilter`][Self::SocketFilter],
+    /// [`SkReuseport`][Self::SkReuseport], and [`SkLookup`][Self::SkLookup]
+    /// types.
+    pub fn is_networking(&self) -> bool {
+        matches!(
+            self,
+            Self::SocketFilter
+                | Self::SchedCls
+                | Self::SchedAct
+                | Self::Xdp
+                | Self::CgroupSkb
+                | Self::CgroupSock
+                | Self::LwtIn
+                | Self::LwtOut
+                | Self::LwtXmit
+                | Self::SockOps
+                | Self::SkSkb
+                | Self::SkMsg
+                | Self::CgroupSockAddr
+                | Self::LwtSeg6local
+                | Self::SkReuseport
+                | Self::FlowDissector
+                | Self::CgroupSockopt
+                | Self::SkLookup
+                | Self::Netfilter
+        )
+    }
+
+    /// Check whether this is a cgroup program type, i.e., one attached to a
+    /// cgroup via [`Program::attach_cgroup`] and friends.
+    ///
+    /// Besides the `Cgroup*` types, this includes [`SockOps`][Self::SockOps].
+    /// [`Lsm`][Self::Lsm] programs may be attached to cgroups as well, but
+    /// usually are not, and so are not included.
+    pub fn is_cgroup(&self) -> bool {
+        matches!(
+            self,
+            Self::CgroupSkb
+                | Self::CgroupSock
+                | Self::SockOps
+                | Self::CgroupDevice
+                | Self::CgroupSockAddr
+                | Self::CgroupSysctl
+                | Self::CgroupSockopt
+        )
+    }
+
+    /// Check whether programs of this type need an attach target to be set
+    /// (see [`OpenProgram::set_attach_target_fd`]) before they can be loaded.
+    ///
+    /// This is the case for [`Tracing`][Self::Tracing], [`Ext`][Self::Ext],
+    /// and [`Lsm`][Self::Lsm] programs, for which the kernel requires the
+    /// BTF ID of the attached to function at load time. libbpf sets the
+    /// target based on the program's section if it names one, e.g.,
+    /// `SEC("fentry/do_unlinkat")`. [`StructOps`][Self::StructOps] programs
+    /// need a target as well, but it is always derived from the struct_ops
+    /// map they are referenced by.
+    pub fn needs_attach_target(&self) -> bool {
+        matches!(self, Self::Tracing | Self::Ext | Self::Lsm)
+    }
+
     /// Detects if host kernel supports this BPF program type
     ///
     /// Make sure the process has required set of CAP_* permissions (or runs as
@@ -297,6 +1694,66 @@ impl ProgramType {
         }
     }
 
+    /// Detects if host kernel supports this BPF program type, like
+    /// [`ProgramType::is_supported`], and additionally report the verifier
+    /// log of loading a minimal program of this type.
+    ///
+    /// libbpf does not expose the log of its own probe, so the log is
+    /// captured by loading the same trivial program (`r0 = 0; exit`) with
+    /// the same load attributes libbpf uses for probing. When probing
+    /// reports a program type as unsupported, the log usually explains why.
+    ///
+    /// Make sure the process has required set of CAP_* permissions (or runs as
+    /// root) when performing feature checking.
+    pub fn is_supported_with_log(&self) -> Result<(bool, String)> {
+        let supported = self.is_supported()?;
+        let log = self.probe_log();
+        Ok((supported, log))
+    }
+
+    fn probe_log(&self) -> String {
+        const LOG_BUF_SIZE: usize = 1 << 16;
+
+        let mut log_buf = vec![0 as c_char; LOG_BUF_SIZE];
+        let mut opts = libbpf_sys::bpf_prog_load_opts {
+            sz: size_of::<libbpf_sys::bpf_prog_load_opts>() as _,
+            log_buf: log_buf.as_mut_ptr(),
+            log_size: LOG_BUF_SIZE as _,
+            log_level: 1,
+            ..Default::default()
+        };
+
+        // Keep in sync with `probe_prog_load` in libbpf's `libbpf_probes.c`.
+        // The kernel version is only checked for kprobes by kernels older
+        // than 5.0, so we don't bother setting it.
+        match self {
+            Self::CgroupSockAddr => {
+                opts.expected_attach_type = libbpf_sys::BPF_CGROUP_INET4_CONNECT
+            }
+            Self::CgroupSockopt => opts.expected_attach_type = libbpf_sys::BPF_CGROUP_GETSOCKOPT,
+            Self::SkLookup => opts.expected_attach_type = libbpf_sys::BPF_SK_LOOKUP,
+            Self::LircMode2 => opts.expected_attach_type = libbpf_sys::BPF_LIRC_MODE2,
+            Self::Tracing => {
+                opts.expected_attach_type = libbpf_sys::BPF_TRACE_FENTRY;
+                opts.attach_btf_id = 1;
+            }
+            Self::Lsm => {
+                opts.expected_attach_type = libbpf_sys::BPF_MODIFY_RETURN;
+                opts.attach_btf_id = 1;
+            }
+            Self::Ext => opts.attach_btf_id = 1,
+            Self::Syscall => opts.prog_flags = libbpf_sys::BPF_F_SLEEPABLE,
+            Self::Netfilter => opts.expected_attach_type = libbpf_sys::BPF_NETFILTER,
+            _ => (),
+        }
+
+        let _result = load_probe_prog(*self as u32, &mut opts);
+
+        util::c_char_slice_to_cstr(&log_buf)
+            .map(|log| log.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
     /// Detects if host kernel supports the use of a given BPF helper from this BPF program type.
     /// * `helper_id` - BPF helper ID (enum bpf_func_id) to check support for
     ///
@@ -311,6 +1768,40 @@ impl ProgramType {
             _ => Err(Error::from_raw_os_error(-ret)),
         }
     }
+
+    /// Retrieve all BPF helpers that the host kernel supports being used from
+    /// this BPF program type.
+    ///
+    /// Only helpers known to the `libbpf-sys` version in use, i.e., with an id
+    /// below [`libbpf_sys::__BPF_FUNC_MAX_ID`], are checked. Helpers added by
+    /// newer kernels will not be reported.
+    ///
+    /// Make sure the process has required set of CAP_* permissions (or runs as
+    /// root) when performing feature checking.
+    pub fn supported_helpers(&self) -> Result<Vec<bpf_func_id>> {
+        // `BPF_FUNC_unspec` is not an actual helper.
+        (libbpf_sys::BPF_FUNC_unspec + 1..libbpf_sys::__BPF_FUNC_MAX_ID)
+            .filter_map(|helper_id| match self.is_helper_supported(helper_id) {
+                Ok(true) => Some(Ok(helper_id)),
+                Ok(false) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for ProgramType {
+    type Err = Error;
+
+    /// Parse a program type from either its kernel name (e.g.,
+    /// `"sched_cls"`) or its variant name (e.g., `"SchedCls"`), ignoring
+    /// case.
+    fn from_str(s: &str) -> Result<Self> {
+        PROGRAM_TYPES
+            .into_iter()
+            .find(|ty| s.eq_ignore_ascii_case(ty.name()) || s.eq_ignore_ascii_case(&ty.to_string()))
+            .ok_or_else(|| Error::with_invalid_data(format!("unknown program type: {s}")))
+    }
 }
 
 /// Attach type of a [`Program`]. Maps to `enum bpf_attach_type` in kernel uapi.
@@ -362,10 +1853,487 @@ pub enum ProgramAttachType {
     SkReuseportSelect,
     SkReuseportSelectOrMigrate,
     PerfEvent,
+    TraceKprobeMulti,
+    LsmCgroup,
+    StructOps,
+    Netfilter,
+    TcxIngress,
+    TcxEgress,
+    TraceUprobeMulti,
     /// See [`MapType::Unknown`][crate::MapType::Unknown]
     Unknown = u32::MAX,
 }
 
+/// All known [`ProgramAttachType`] variants, excluding
+/// [`ProgramAttachType::Unknown`].
+const PROGRAM_ATTACH_TYPES: [ProgramAttachType; 49] = [
+    ProgramAttachType::CgroupInetIngress,
+    ProgramAttachType::CgroupInetEgress,
+    ProgramAttachType::CgroupInetSockCreate,
+    ProgramAttachType::CgroupSockOps,
+    ProgramAttachType::SkSkbStreamParser,
+    ProgramAttachType::SkSkbStreamVerdict,
+    ProgramAttachType::CgroupDevice,
+    ProgramAttachType::SkMsgVerdict,
+    ProgramAttachType::CgroupInet4Bind,
+    ProgramAttachType::CgroupInet6Bind,
+    ProgramAttachType::CgroupInet4Connect,
+    ProgramAttachType::CgroupInet6Connect,
+    ProgramAttachType::CgroupInet4PostBind,
+    ProgramAttachType::CgroupInet6PostBind,
+    ProgramAttachType::CgroupUdp4Sendmsg,
+    ProgramAttachType::CgroupUdp6Sendmsg,
+    ProgramAttachType::LircMode2,
+    ProgramAttachType::FlowDissector,
+    ProgramAttachType::CgroupSysctl,
+    ProgramAttachType::CgroupUdp4Recvmsg,
+    ProgramAttachType::CgroupUdp6Recvmsg,
+    ProgramAttachType::CgroupGetsockopt,
+    ProgramAttachType::CgroupSetsockopt,
+    ProgramAttachType::TraceRawTp,
+    ProgramAttachType::TraceFentry,
+    ProgramAttachType::TraceFexit,
+    ProgramAttachType::ModifyReturn,
+    ProgramAttachType::LsmMac,
+    ProgramAttachType::TraceIter,
+    ProgramAttachType::CgroupInet4Getpeername,
+    ProgramAttachType::CgroupInet6Getpeername,
+    ProgramAttachType::CgroupInet4Getsockname,
+    ProgramAttachType::CgroupInet6Getsockname,
+    ProgramAttachType::XdpDevmap,
+    ProgramAttachType::CgroupInetSockRelease,
+    ProgramAttachType::XdpCpumap,
+    ProgramAttachType::SkLookup,
+    ProgramAttachType::Xdp,
+    ProgramAttachType::SkSkbVerdict,
+    ProgramAttachType::SkReuseportSelect,
+    ProgramAttachType::SkReuseportSelectOrMigrate,
+    ProgramAttachType::PerfEvent,
+    ProgramAttachType::TraceKprobeMulti,
+    ProgramAttachType::LsmCgroup,
+    ProgramAttachType::StructOps,
+    ProgramAttachType::Netfilter,
+    ProgramAttachType::TcxIngress,
+    ProgramAttachType::TcxEgress,
+    ProgramAttachType::TraceUprobeMulti,
+];
+
+/// The kernel internal error code reporting an unsupported operation,
+/// which is not part of the UAPI headers and, hence, not covered by `libc`.
+const ENOTSUPP: i32 = 524;
+
+/// How support for a [`ProgramAttachType`] is probed for.
+enum AttachTypeProbe {
+    /// Load a minimal program of the given type, with the attach type as
+    /// expected attach type.
+    Load(ProgramType),
+    /// Like [`AttachTypeProbe::Load`], but the kernel only started
+    /// validating the expected attach type along with introducing the
+    /// attach type, so it is additionally checked that a mismatching
+    /// expected attach type gets rejected.
+    LoadValidated(ProgramType),
+    /// Like [`AttachTypeProbe::Load`], but with a bogus attach BTF ID,
+    /// expecting the load to fail with `EINVAL` and the verifier log to
+    /// contain the given message.
+    LoadBtf(ProgramType, &'static str),
+    /// Like [`AttachTypeProbe::Load`], but expecting the load to fail with
+    /// the given error.
+    LoadErr(ProgramType, i32),
+    /// Load a minimal program like [`AttachTypeProbe::Load`] and link it to
+    /// a bogus target, expecting the kernel to reject the target with the
+    /// given error rather than the attach type with `EINVAL`.
+    Link(ProgramType, i32),
+}
+
+impl ProgramAttachType {
+    /// Detects if host kernel supports this attach type.
+    ///
+    /// Depending on the attach type, one of the following is checked:
+    /// - for attach types the kernel validates when loading a program,
+    ///   such as those of cgroup, socket lookup, and `SO_REUSEPORT`
+    ///   programs, that a minimal program expecting the attach type loads
+    /// - for tracing and LSM attach types, that the kernel accepts the
+    ///   attach type and merely complains about the program's bogus attach
+    ///   target
+    /// - for attach types only used when creating a link, such as
+    ///   [`TcxIngress`][Self::TcxIngress] or
+    ///   [`TraceKprobeMulti`][Self::TraceKprobeMulti], that the kernel
+    ///   rejects linking to a bogus target rather than the attach type
+    /// - for attach types introduced along with their program type, such
+    ///   as [`CgroupDevice`][Self::CgroupDevice], that the program type
+    ///   is supported
+    ///
+    /// [`XdpDevmap`][Self::XdpDevmap], [`XdpCpumap`][Self::XdpCpumap],
+    /// [`SkSkbVerdict`][Self::SkSkbVerdict],
+    /// [`ModifyReturn`][Self::ModifyReturn], and
+    /// [`TraceIter`][Self::TraceIter] are newer than their program type,
+    /// but the kernel can't be made to tell whether it knows them without
+    /// actually using them. Probing them results in an error of kind
+    /// [`ErrorKind::Unsupported`]. [`LsmCgroup`][Self::LsmCgroup] is
+    /// probed by linking a program attached to the `socket_bind` LSM hook,
+    /// which requires kernel BTF to be available.
+    ///
+    /// Make sure the process has required set of CAP_* permissions (or runs as
+    /// root) when performing feature checking. Loading programs generally
+    /// requires `CAP_BPF`, and, depending on the program type,
+    /// `CAP_PERFMON` or `CAP_NET_ADMIN`. Missing permissions result in the
+    /// attach type being reported as unsupported.
+    ///
+    /// Probing [`ProgramAttachType::Unknown`] results in an error.
+    pub fn is_supported(&self) -> Result<bool> {
+        let probe = match self {
+            Self::CgroupInetIngress | Self::CgroupInetEgress => {
+                AttachTypeProbe::Load(ProgramType::CgroupSkb)
+            }
+            Self::CgroupInetSockCreate
+            | Self::CgroupInet4PostBind
+            | Self::CgroupInet6PostBind
+            | Self::CgroupInetSockRelease => AttachTypeProbe::Load(ProgramType::CgroupSock),
+            Self::CgroupSockOps => AttachTypeProbe::Load(ProgramType::SockOps),
+            Self::SkSkbStreamParser | Self::SkSkbStreamVerdict => {
+                AttachTypeProbe::Load(ProgramType::SkSkb)
+            }
+            Self::CgroupDevice => AttachTypeProbe::Load(ProgramType::CgroupDevice),
+            Self::SkMsgVerdict => AttachTypeProbe::Load(ProgramType::SkMsg),
+            Self::CgroupInet4Bind
+            | Self::CgroupInet6Bind
+            | Self::CgroupInet4Connect
+            | Self::CgroupInet6Connect
+            | Self::CgroupUdp4Sendmsg
+            | Self::CgroupUdp6Sendmsg
+            | Self::CgroupUdp4Recvmsg
+            | Self::CgroupUdp6Recvmsg
+            | Self::CgroupInet4Getpeername
+            | Self::CgroupInet6Getpeername
+            | Self::CgroupInet4Getsockname
+            | Self::CgroupInet6Getsockname => AttachTypeProbe::Load(ProgramType::CgroupSockAddr),
+            Self::LircMode2 => AttachTypeProbe::Load(ProgramType::LircMode2),
+            Self::FlowDissector => AttachTypeProbe::Load(ProgramType::FlowDissector),
+            Self::CgroupSysctl => AttachTypeProbe::Load(ProgramType::CgroupSysctl),
+            Self::CgroupGetsockopt | Self::CgroupSetsockopt => {
+                AttachTypeProbe::Load(ProgramType::CgroupSockopt)
+            }
+            // Tracing and LSM programs need a BTF ID to attach to. We
+            // provide a bogus one and check that the kernel complains about
+            // just that, similar to what libbpf does when probing program
+            // types.
+            Self::TraceRawTp => {
+                AttachTypeProbe::LoadBtf(ProgramType::Tracing, "attach_btf_id 1 is not a typedef")
+            }
+            Self::TraceFentry | Self::TraceFexit => {
+                AttachTypeProbe::LoadBtf(ProgramType::Tracing, "attach_btf_id 1 is not a function")
+            }
+            Self::LsmMac => {
+                AttachTypeProbe::LoadBtf(ProgramType::Lsm, "attach_btf_id 1 is not a function")
+            }
+            Self::Xdp => AttachTypeProbe::Load(ProgramType::Xdp),
+            Self::SkLookup => AttachTypeProbe::Load(ProgramType::SkLookup),
+            Self::SkReuseportSelect => AttachTypeProbe::Load(ProgramType::SkReuseport),
+            Self::SkReuseportSelectOrMigrate => {
+                AttachTypeProbe::LoadValidated(ProgramType::SkReuseport)
+            }
+            // Struct ops programs can't be loaded without a struct type to
+            // implement, in which case the kernel reports `ENOTSUPP`.
+            Self::StructOps => AttachTypeProbe::LoadErr(ProgramType::StructOps, ENOTSUPP),
+            Self::PerfEvent => AttachTypeProbe::Link(ProgramType::PerfEvent, libc::EBADF),
+            Self::TraceKprobeMulti => AttachTypeProbe::Link(ProgramType::Kprobe, libc::EFAULT),
+            Self::TraceUprobeMulti => AttachTypeProbe::Link(ProgramType::Kprobe, libc::EBADF),
+            Self::Netfilter => AttachTypeProbe::Link(ProgramType::Netfilter, libc::ERANGE),
+            Self::TcxIngress | Self::TcxEgress => {
+                AttachTypeProbe::Link(ProgramType::SchedCls, libc::ENODEV)
+            }
+            Self::LsmCgroup => AttachTypeProbe::Link(ProgramType::Lsm, libc::EBADF),
+            Self::XdpDevmap
+            | Self::XdpCpumap
+            | Self::SkSkbVerdict
+            | Self::ModifyReturn
+            | Self::TraceIter => {
+                return Err(Error::with_io_error(
+                    io::ErrorKind::Unsupported,
+                    format!("support for attach type {self} cannot be probed"),
+                ))
+            }
+            Self::Unknown => return Err(Error::from_raw_os_error(libc::EINVAL)),
+        };
+
+        let opts = libbpf_sys::bpf_prog_load_opts {
+            sz: size_of::<libbpf_sys::bpf_prog_load_opts>() as _,
+            expected_attach_type: self.clone() as u32,
+            ..Default::default()
+        };
+
+        match probe {
+            AttachTypeProbe::Load(prog_type) => {
+                Ok(load_probe_prog(prog_type as u32, &mut opts.clone()).is_ok())
+            }
+            AttachTypeProbe::LoadValidated(prog_type) => {
+                // No program type validating its expected attach type
+                // accepts tracing attach types.
+                let mut mismatch_opts = libbpf_sys::bpf_prog_load_opts {
+                    expected_attach_type: libbpf_sys::BPF_TRACE_FENTRY,
+                    ..opts
+                };
+                let supported = load_probe_prog(prog_type as u32, &mut opts.clone()).is_ok()
+                    && load_probe_prog(prog_type as u32, &mut mismatch_opts).is_err();
+                Ok(supported)
+            }
+            AttachTypeProbe::LoadBtf(prog_type, exp_msg) => {
+                const LOG_BUF_SIZE: usize = 4096;
+
+                let mut log_buf = vec![0 as c_char; LOG_BUF_SIZE];
+                let mut opts = libbpf_sys::bpf_prog_load_opts {
+                    log_buf: log_buf.as_mut_ptr(),
+                    log_size: LOG_BUF_SIZE as _,
+                    log_level: 1,
+                    attach_btf_id: 1,
+                    ..opts
+                };
+                let result = load_probe_prog(prog_type as u32, &mut opts);
+                let supported = match result {
+                    Ok(_fd) => false,
+                    Err(err) => {
+                        err.raw_os_error() == Some(libc::EINVAL)
+                            && util::c_char_slice_to_cstr(&log_buf)
+                                .map(|log| log.to_string_lossy().contains(exp_msg))
+                                .unwrap_or(false)
+                    }
+                };
+                Ok(supported)
+            }
+            AttachTypeProbe::LoadErr(prog_type, exp_err) => {
+                let result = load_probe_prog(prog_type as u32, &mut opts.clone());
+                let supported = match result {
+                    Ok(_fd) => false,
+                    Err(err) => err.raw_os_error() == Some(exp_err),
+                };
+                Ok(supported)
+            }
+            AttachTypeProbe::Link(prog_type, exp_err) => self.probe_link(prog_type, opts, exp_err),
+        }
+    }
+
+    /// Load a minimal program of type `prog_type` expecting this attach
+    /// type and try linking it to a bogus target, checking that the kernel
+    /// fails with `exp_err`.
+    fn probe_link(
+        &self,
+        prog_type: ProgramType,
+        mut load_opts: libbpf_sys::bpf_prog_load_opts,
+        exp_err: i32,
+    ) -> Result<bool> {
+        if let Self::LsmCgroup = self {
+            let ret = unsafe {
+                libbpf_sys::libbpf_find_vmlinux_btf_id(
+                    b"socket_bind\0".as_ptr().cast(),
+                    libbpf_sys::BPF_LSM_CGROUP,
+                )
+            };
+            // Without BPF LSM support, the kernel lacks the hook.
+            if ret == -libc::ENOENT {
+                return Ok(false);
+            }
+            let btf_id = util::parse_ret_i32(ret)
+                .context("failed to find `socket_bind` LSM hook in kernel BTF")?;
+            load_opts.attach_btf_id = btf_id as _;
+        }
+
+        let prog_fd = match load_probe_prog(prog_type as u32, &mut load_opts) {
+            Ok(fd) => fd,
+            Err(_err) => return Ok(false),
+        };
+
+        let offset: c_ulong = 0;
+        let mut opts = libbpf_sys::bpf_link_create_opts {
+            sz: size_of::<libbpf_sys::bpf_link_create_opts>() as _,
+            ..Default::default()
+        };
+        // For each attach type, pick a target that a supporting kernel
+        // rejects with an error other than `EINVAL`, which is what an
+        // unsupporting kernel reports for the attach type.
+        let target_fd = match self {
+            // Interface index 0 does not exist, reported as `ENODEV`.
+            Self::TcxIngress | Self::TcxEgress => 0,
+            // The address of the single function to attach to can't be
+            // read, reported as `EFAULT`.
+            Self::TraceKprobeMulti => {
+                opts.__bindgen_anon_1.kprobe_multi =
+                    libbpf_sys::bpf_link_create_opts__bindgen_ty_1__bindgen_ty_2 {
+                        cnt: 1,
+                        addrs: NonNull::<c_ulong>::dangling().as_ptr(),
+                        ..Default::default()
+                    };
+                -1
+            }
+            // A directory can't be uprobed, reported as `EBADF`. This
+            // matches libbpf's own probe.
+            Self::TraceUprobeMulti => {
+                opts.__bindgen_anon_1.uprobe_multi =
+                    libbpf_sys::bpf_link_create_opts__bindgen_ty_1__bindgen_ty_3 {
+                        cnt: 1,
+                        path: b"/\0".as_ptr().cast(),
+                        offsets: &offset,
+                        ..Default::default()
+                    };
+                -1
+            }
+            // The lowest priority is reserved, reported as `ERANGE`.
+            Self::Netfilter => {
+                opts.__bindgen_anon_1.netfilter =
+                    libbpf_sys::bpf_link_create_opts__bindgen_ty_1__bindgen_ty_5 {
+                        pf: libc::NFPROTO_IPV4 as _,
+                        hooknum: libc::NF_INET_LOCAL_IN as _,
+                        priority: i32::MIN,
+                        flags: 0,
+                    };
+                -1
+            }
+            // An invalid perf event or cgroup file descriptor, reported as
+            // `EBADF`.
+            _ => -1,
+        };
+
+        let ret = unsafe {
+            libbpf_sys::bpf_link_create(prog_fd.as_raw_fd(), target_fd, self.clone() as u32, &opts)
+        };
+        if ret >= 0 {
+            // SAFETY
+            // A file descriptor coming from the bpf_link_create function is always suitable for
+            // ownership and can be cleaned up with close.
+            let _link = unsafe { OwnedFd::from_raw_fd(ret) };
+            return Ok(true);
+        }
+        Ok(-ret == exp_err)
+    }
+
+    /// The kernel's name for this attach type, e.g.,
+    /// `"cgroup_inet_ingress"` for [`ProgramAttachType::CgroupInetIngress`].
+    ///
+    /// [`ProgramAttachType::Unknown`] is reported as `"unknown"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CgroupInetIngress => "cgroup_inet_ingress",
+            Self::CgroupInetEgress => "cgroup_inet_egress",
+            Self::CgroupInetSockCreate => "cgroup_inet_sock_create",
+            Self::CgroupSockOps => "cgroup_sock_ops",
+            Self::SkSkbStreamParser => "sk_skb_stream_parser",
+            Self::SkSkbStreamVerdict => "sk_skb_stream_verdict",
+            Self::CgroupDevice => "cgroup_device",
+            Self::SkMsgVerdict => "sk_msg_verdict",
+            Self::CgroupInet4Bind => "cgroup_inet4_bind",
+            Self::CgroupInet6Bind => "cgroup_inet6_bind",
+            Self::CgroupInet4Connect => "cgroup_inet4_connect",
+            Self::CgroupInet6Connect => "cgroup_inet6_connect",
+            Self::CgroupInet4PostBind => "cgroup_inet4_post_bind",
+            Self::CgroupInet6PostBind => "cgroup_inet6_post_bind",
+            Self::CgroupUdp4Sendmsg => "cgroup_udp4_sendmsg",
+            Self::CgroupUdp6Sendmsg => "cgroup_udp6_sendmsg",
+            Self::LircMode2 => "lirc_mode2",
+            Self::FlowDissector => "flow_dissector",
+            Self::CgroupSysctl => "cgroup_sysctl",
+            Self::CgroupUdp4Recvmsg => "cgroup_udp4_recvmsg",
+            Self::CgroupUdp6Recvmsg => "cgroup_udp6_recvmsg",
+            Self::CgroupGetsockopt => "cgroup_getsockopt",
+            Self::CgroupSetsockopt => "cgroup_setsockopt",
+            Self::TraceRawTp => "trace_raw_tp",
+            Self::TraceFentry => "trace_fentry",
+            Self::TraceFexit => "trace_fexit",
+            Self::ModifyReturn => "modify_return",
+            Self::LsmMac => "lsm_mac",
+            Self::TraceIter => "trace_iter",
+            Self::CgroupInet4Getpeername => "cgroup_inet4_getpeername",
+            Self::CgroupInet6Getpeername => "cgroup_inet6_getpeername",
+            Self::CgroupInet4Getsockname => "cgroup_inet4_getsockname",
+            Self::CgroupInet6Getsockname => "cgroup_inet6_getsockname",
+            Self::XdpDevmap => "xdp_devmap",
+            Self::CgroupInetSockRelease => "cgroup_inet_sock_release",
+            Self::XdpCpumap => "xdp_cpumap",
+            Self::SkLookup => "sk_lookup",
+            Self::Xdp => "xdp",
+            Self::SkSkbVerdict => "sk_skb_verdict",
+            Self::SkReuseportSelect => "sk_reuseport_select",
+            Self::SkReuseportSelectOrMigrate => "sk_reuseport_select_or_migrate",
+            Self::PerfEvent => "perf_event",
+            Self::TraceKprobeMulti => "trace_kprobe_multi",
+            Self::LsmCgroup => "lsm_cgroup",
+            Self::StructOps => "struct_ops",
+            Self::Netfilter => "netfilter",
+            Self::TcxIngress => "tcx_ingress",
+            Self::TcxEgress => "tcx_egress",
+            Self::TraceUprobeMulti => "trace_uprobe_multi",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl FromStr for ProgramAttachType {
+    type Err = Error;
+
+    /// Parse an attach type from either its kernel name (e.g.,
+    /// `"cgroup_inet_ingress"`) or its variant name (e.g.,
+    /// `"CgroupInetIngress"`), ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        PROGRAM_ATTACH_TYPES
+            .into_iter()
+            .find(|ty| s.eq_ignore_ascii_case(ty.name()) || s.eq_ignore_ascii_case(&ty.to_string()))
+            .ok_or_else(|| Error::with_invalid_data(format!("unknown attach type: {s}")))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ProgramType {
+    /// Serialize the program type as its kernel name, e.g., `"sched_cls"`.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ProgramType {
+    /// Deserialize a program type from any of the names accepted by
+    /// [`ProgramType::from_str`], or from `"unknown"`.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        if name.eq_ignore_ascii_case(Self::Unknown.name()) {
+            return Ok(Self::Unknown);
+        }
+        Self::from_str(&name).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ProgramAttachType {
+    /// Serialize the attach type as its kernel name, e.g.,
+    /// `"cgroup_inet_ingress"`.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ProgramAttachType {
+    /// Deserialize an attach type from any of the names accepted by
+    /// [`ProgramAttachType::from_str`], or from `"unknown"`.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        if name.eq_ignore_ascii_case(Self::Unknown.name()) {
+            return Ok(Self::Unknown);
+        }
+        Self::from_str(&name).map_err(D::Error::custom)
+    }
+}
+
 /// The input a program accepts.
 ///
 /// This type is mostly used in conjunction with the [`Program::test_run`]
@@ -384,11 +2352,112 @@ pub struct Input<'dat> {
     pub cpu: u32,
     /// The 'flags' value passed to the kernel.
     pub flags: u32,
+    /// The number of times the kernel runs the program, e.g., for
+    /// benchmarking purposes. `0` and `1` both run it once.
+    pub repeat: u32,
+    /// The number of packets processed per batch by certain program types,
+    /// e.g., XDP programs run with `BPF_F_TEST_XDP_LIVE_FRAMES`. `0` selects
+    /// the kernel's default.
+    pub batch_size: u32,
+    /// Run an XDP program in live frames mode, i.e., with
+    /// `BPF_F_TEST_XDP_LIVE_FRAMES` set.
+    ///
+    /// In this mode `data_in` is used as a template for the packets the
+    /// kernel generates. Packets are run through the program `repeat` times,
+    /// in batches of `batch_size`, and the resulting verdicts are acted upon,
+    /// e.g., `XDP_TX` and `XDP_REDIRECT` actually transmit packets and
+    /// `XDP_PASS` hands them to the networking stack. Neither `context_out`
+    /// nor `data_out` may be provided.
+    pub xdp_live_frames: bool,
+    /// Capture the output data into a buffer allocated by
+    /// [`Program::test_run`], if no `data_out` buffer is provided.
+    ///
+    /// The buffer is sized as the length of `data_in` plus 4 KiB of slack,
+    /// accommodating programs that grow the packet, e.g., via
+    /// `bpf_xdp_adjust_head` (which can claim up to 256 bytes of headroom) or
+    /// `bpf_xdp_adjust_tail`. The captured data is reported in
+    /// [`Output::captured_data`].
+    pub capture_data_out: bool,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub _non_exhaustive: (),
 }
 
+impl<'dat> Input<'dat> {
+    /// Create an empty [`Input`], to be filled in by the chained setters.
+    ///
+    /// ```no_run
+    /// # use libbpf_rs::ProgramInput;
+    /// let data = [0u8; 64];
+    /// let input = ProgramInput::new().data_in(&data).repeat(10);
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the input context to provide.
+    pub fn context_in(mut self, context_in: &'dat [u8]) -> Self {
+        self.context_in = Some(context_in);
+        self
+    }
+
+    /// Set the output context buffer provided to the program.
+    pub fn context_out(mut self, context_out: &'dat mut [u8]) -> Self {
+        self.context_out = Some(context_out);
+        self
+    }
+
+    /// Set additional data to provide to the program.
+    pub fn data_in(mut self, data_in: &'dat [u8]) -> Self {
+        self.data_in = Some(data_in);
+        self
+    }
+
+    /// Set the output data buffer provided to the program.
+    pub fn data_out(mut self, data_out: &'dat mut [u8]) -> Self {
+        self.data_out = Some(data_out);
+        self
+    }
+
+    /// Set the 'cpu' value passed to the kernel.
+    pub fn cpu(mut self, cpu: u32) -> Self {
+        self.cpu = cpu;
+        self
+    }
+
+    /// Set the 'flags' value passed to the kernel.
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set the number of times the kernel runs the program.
+    pub fn repeat(mut self, repeat: u32) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Set the number of packets processed per batch.
+    pub fn batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set whether to run an XDP program in live frames mode. Please refer
+    /// to [`Input::xdp_live_frames`] for details.
+    pub fn xdp_live_frames(mut self, xdp_live_frames: bool) -> Self {
+        self.xdp_live_frames = xdp_live_frames;
+        self
+    }
+
+    /// Set whether to capture the output data into an automatically sized
+    /// buffer. Please refer to [`Input::capture_data_out`] for details.
+    pub fn capture_data_out(mut self, capture_data_out: bool) -> Self {
+        self.capture_data_out = capture_data_out;
+        self
+    }
+}
+
 /// The output a program produces.
 ///
 /// This type is mostly used in conjunction with the [`Program::test_run`]
@@ -401,6 +2470,92 @@ pub struct Output<'dat> {
     pub context: Option<&'dat mut [u8]>,
     /// Output data filled by the program.
     pub data: Option<&'dat mut [u8]>,
+    /// The average duration of a single run of the program, as measured by
+    /// the kernel.
+    pub duration: Duration,
+    /// Output data filled by the program, if requested via
+    /// [`Input::capture_data_out`] without providing a `data_out` buffer.
+    pub captured_data: Option<Vec<u8>>,
+    /// The struct is non-exhaustive and open to extension.
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Output<'_> {
+    /// The value returned by the program, interpreted as a signed integer.
+    ///
+    /// Several program types, e.g., LSM, `fmod_ret`, and syscall programs,
+    /// return negative values such as `-EPERM`. These are reported as large
+    /// unsigned numbers in [`Output::return_value`].
+    pub fn return_value_signed(&self) -> i32 {
+        self.return_value as i32
+    }
+}
+
+/// A reusable test run of a [`Program`], e.g., for benchmarking purposes.
+///
+/// Created via [`Program::test_runner`]. The input and output buffers
+/// provided at creation time are used for every run.
+///
+/// ```no_run
+/// # use libbpf_rs::Program;
+/// # use libbpf_rs::ProgramInput;
+/// # fn bench(prog: &Program) -> libbpf_rs::Result<()> {
+/// let data = [0u8; 64];
+/// let mut runner = prog.test_runner(ProgramInput::new().data_in(&data))?;
+/// for _ in 0..1_000_000 {
+///     let _retval = runner.run()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TestRunner<'prog, 'dat> {
+    fd: BorrowedFd<'prog>,
+    opts: libbpf_sys::bpf_test_run_opts,
+    /// The size of the output context buffer, which the kernel overwrites
+    /// with the size it filled on every run.
+    ctx_size_out: u32,
+    /// The size of the output data buffer, likewise overwritten.
+    data_size_out: u32,
+    _data: PhantomData<&'dat mut [u8]>,
+}
+
+impl TestRunner<'_, '_> {
+    /// Test run the program once, returning the value it returned.
+    ///
+    /// Output buffers, if any, hold the output of the latest run.
+    pub fn run(&mut self) -> Result<u32> {
+        self.opts.ctx_size_out = self.ctx_size_out;
+        self.opts.data_size_out = self.data_size_out;
+
+        let rc = unsafe { libbpf_sys::bpf_prog_test_run_opts(self.fd.as_raw_fd(), &mut self.opts) };
+        let () = util::parse_ret(rc)?;
+        Ok(self.opts.retval)
+    }
+
+    /// The average duration of a single run of the program during the
+    /// latest [`TestRunner::run`], as measured by the kernel.
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(self.opts.duration.into())
+    }
+}
+
+/// The instruction counts of a loaded [`Program`] at its various stages, as
+/// reported by [`Program::insn_counts`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InsnCounts {
+    /// The number of instructions libbpf handed to the kernel, after
+    /// processing the BPF object file. See [`Program::insn_cnt`].
+    pub libbpf: usize,
+    /// The number of instructions the verifier processed, accounting for
+    /// all explored paths. See [`Program::verified_insns`].
+    pub verified: u32,
+    /// The number of instructions of the program as translated by the
+    /// kernel, after the verifier patched and removed instructions, e.g.,
+    /// dead code. See [`Program::xlated_insns`]. Zero if the caller lacks
+    /// the privileges to retrieve the translated instructions.
+    pub xlated: usize,
     /// The struct is non-exhaustive and open to extension.
     #[doc(hidden)]
     pub _non_exhaustive: (),
@@ -411,6 +2566,11 @@ pub struct Output<'dat> {
 /// This struct is not safe to clone because the underlying libbpf resource cannot currently
 /// be protected from data races.
 ///
+/// To obtain a second, owned handle to the loaded program, e.g., to move it to a different
+/// thread, duplicate its file descriptor via [`AsFd::as_fd`] and
+/// [`BorrowedFd::try_clone_to_owned`]. Both file descriptors refer to the same kernel object and
+/// the program stays loaded for as long as either is open.
+///
 /// If you attempt to attach a `Program` with the wrong attach method, the `attach_*`
 /// method will fail with the appropriate error.
 #[derive(Debug)]
@@ -418,6 +2578,8 @@ pub struct Program {
     pub(crate) ptr: NonNull<libbpf_sys::bpf_program>,
     name: String,
     section: String,
+    pin_paths: Vec<PathBuf>,
+    pub(crate) log_buf: Option<Box<[c_char]>>,
 }
 
 impl AsFd for Program {
@@ -443,7 +2605,13 @@ impl Program {
         let section = unsafe { libbpf_sys::bpf_program__section_name(ptr.as_ptr()) };
         let section = util::c_ptr_to_string(section)?;
 
-        Ok(Program { ptr, name, section })
+        Ok(Program {
+            ptr,
+            name,
+            section,
+            pin_paths: Vec::new(),
+            log_buf: None,
+        })
     }
 
     /// Retrieve the program's name.
@@ -464,30 +2632,508 @@ impl Program {
         }
     }
 
-    /// Returns program fd by id
-    pub fn get_fd_by_id(id: u32) -> Result<OwnedFd> {
-        let ret = unsafe { libbpf_sys::bpf_prog_get_fd_by_id(id) };
-        let fd = util::parse_ret_i32(ret)?;
-        // SAFETY
-        // A file descriptor coming from the bpf_prog_get_fd_by_id function is always suitable for
-        // ownership and can be cleaned up with close.
-        Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+    /// Retrieve the kernel's name for the type of the program, e.g.,
+    /// `"sched_cls"`. See [`ProgramType::name`].
+    pub fn prog_type_name(&self) -> &'static str {
+        self.prog_type().name()
+    }
+
+    /// Unload the program from the kernel, closing its file descriptor.
+    pub(crate) fn unload(self) {
+        unsafe { libbpf_sys::bpf_program__unload(self.ptr.as_ptr()) }
+    }
+
+    /// Retrieve the verifier log written while loading the program, if a log
+    /// buffer was provided via [`OpenProgram::set_log_buf`].
+    pub fn log_buf(&self) -> Option<Cow<'_, str>> {
+        let log_buf = self.log_buf.as_ref()?;
+        util::c_char_slice_to_cstr(log_buf).map(CStr::to_string_lossy)
+    }
+
+    /// Returns program fd by id
+    pub fn get_fd_by_id(id: u32) -> Result<OwnedFd> {
+        let ret = unsafe { libbpf_sys::bpf_prog_get_fd_by_id(id) };
+        let fd = util::parse_ret_i32(ret)?;
+        // SAFETY
+        // A file descriptor coming from the bpf_prog_get_fd_by_id function is always suitable for
+        // ownership and can be cleaned up with close.
+        Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+    }
+
+    /// Retrieve information about the loaded program with the given id,
+    /// including the IDs of the maps it references.
+    ///
+    /// This is the counterpart of [`Program::info`] for arbitrary programs
+    /// running on the system, which can't be represented as a [`Program`],
+    /// as the latter is always part of an [`Object`][crate::Object].
+    pub fn info_by_id(id: u32) -> Result<ProgramInfo> {
+        let fd = Self::get_fd_by_id(id)?;
+        let opts = ProgInfoQueryOptions::default().include_map_ids(true);
+        ProgramInfo::load_from_fd(fd.as_fd(), &opts)
+    }
+
+    /// Returns the fd of the first loaded program with the given name.
+    ///
+    /// The kernel truncates program names to 15 characters
+    /// (`BPF_OBJ_NAME_LEN - 1`), so `name` is compared in its truncated form
+    /// and different programs sharing the same prefix cannot be told apart.
+    /// An error of kind [`ErrorKind::NotFound`] is returned if no such
+    /// program exists.
+    pub fn get_fd_by_name(name: &str) -> Result<OwnedFd> {
+        let max_len = libbpf_sys::BPF_OBJ_NAME_LEN as usize - 1;
+        let name = &name.as_bytes()[..name.len().min(max_len)];
+        let opts = ProgInfoQueryOptions::default();
+
+        let mut id = 0;
+        loop {
+            let ret = unsafe { libbpf_sys::bpf_prog_get_next_id(id, &mut id) };
+            if ret == -libc::ENOENT {
+                break;
+            }
+            let () = util::parse_ret(ret)?;
+
+            let fd = match Self::get_fd_by_id(id) {
+                Ok(fd) => fd,
+                // The program may have been unloaded in the meantime.
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            let info = ProgramInfo::load_from_fd(fd.as_fd(), &opts)?;
+            if info.name.as_bytes() == name {
+                return Ok(fd);
+            }
+        }
+
+        Err(Error::with_io_error(
+            io::ErrorKind::NotFound,
+            format!(
+                "no program with name `{}` found",
+                String::from_utf8_lossy(name)
+            ),
+        ))
+    }
+
+    /// Returns the fd of the program pinned to bpffs at `path`, e.g., via
+    /// [`Program::pin`].
+    ///
+    /// If the object pinned at `path` is not a program, an error of kind
+    /// [`ErrorKind::InvalidData`] is reported. Use [`Program::get_id_by_fd`]
+    /// and [`query::ProgInfoIter`][crate::query::ProgInfoIter] to look up
+    /// further information about it.
+    pub fn get_fd_by_pinned_path<P: AsRef<Path>>(path: P) -> Result<OwnedFd> {
+        let path = path.as_ref();
+        let path_c = util::path_to_cstring(path)?;
+        let ret = unsafe { libbpf_sys::bpf_obj_get(path_c.as_ptr()) };
+        let fd = util::parse_ret_i32(ret)?;
+        // SAFETY
+        // A file descriptor coming from the bpf_obj_get function is always suitable for
+        // ownership and can be cleaned up with close.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // Any type of BPF object can be pinned, so make sure that we got a
+        // program.
+        let link = fs::read_link(format!("/proc/self/fd/{}", fd.as_raw_fd()))?;
+        if link.as_os_str() != "anon_inode:bpf-prog" {
+            return Err(Error::with_invalid_data(format!(
+                "{} does not refer to a program",
+                path.display()
+            )));
+        }
+        Ok(fd)
+    }
+
+    /// Returns program id by fd
+    pub fn get_id_by_fd(fd: BorrowedFd<'_>) -> Result<u32> {
+        let mut prog_info = libbpf_sys::bpf_prog_info::default();
+        let prog_info_ptr: *mut libbpf_sys::bpf_prog_info = &mut prog_info;
+        let mut len = size_of::<libbpf_sys::bpf_prog_info>() as u32;
+        let ret = unsafe {
+            libbpf_sys::bpf_obj_get_info_by_fd(
+                fd.as_raw_fd(),
+                prog_info_ptr as *mut c_void,
+                &mut len,
+            )
+        };
+        util::parse_ret(ret)?;
+        Ok(prog_info.id)
+    }
+
+    /// Retrieve the ids of the programs attached to the
+    /// [cgroup](https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html)
+    /// referenced by `cgroup_fd` with the given attach type.
+    ///
+    /// An empty vector is returned if no program is attached.
+    pub fn query_cgroup(
+        cgroup_fd: BorrowedFd<'_>,
+        attach_type: ProgramAttachType,
+    ) -> Result<Vec<u32>> {
+        let attach_type = attach_type as u32;
+        let mut prog_ids = Vec::new();
+        loop {
+            let mut prog_cnt = prog_ids.len() as u32;
+            let ret = unsafe {
+                libbpf_sys::bpf_prog_query(
+                    cgroup_fd.as_raw_fd(),
+                    attach_type,
+                    0,
+                    ptr::null_mut(),
+                    if prog_ids.is_empty() {
+                        ptr::null_mut()
+                    } else {
+                        prog_ids.as_mut_ptr()
+                    },
+                    &mut prog_cnt,
+                )
+            };
+            // More programs may have been attached since we retrieved the
+            // count, in which case we just try again with more space.
+            if ret == -libc::ENOSPC {
+                prog_ids.resize(prog_cnt as usize, 0);
+                continue;
+            }
+            let () = util::parse_ret(ret)?;
+
+            let prog_cnt = prog_cnt as usize;
+            if prog_cnt > prog_ids.len() {
+                // We only learned about the count so far; fetch the ids.
+                prog_ids.resize(prog_cnt, 0);
+                continue;
+            }
+            prog_ids.truncate(prog_cnt);
+            break Ok(prog_ids);
+        }
+    }
+
+    /// Retrieve the programs attached to the TCX hook `attach_point` of the
+    /// network interface with index `ifindex`, in the order they are run.
+    ///
+    /// Use [`TcxOpts::before`] and [`TcxOpts::after`] to control where in
+    /// this order a program is attached.
+    pub fn query_tcx(ifindex: i32, attach_point: TcxAttachPoint) -> Result<TcxChain> {
+        let attach_type = match attach_point {
+            TcxAttachPoint::Ingress => libbpf_sys::BPF_TCX_INGRESS,
+            TcxAttachPoint::Egress => libbpf_sys::BPF_TCX_EGRESS,
+        };
+        let mut prog_ids = Vec::<u32>::new();
+        let mut link_ids = Vec::<u32>::new();
+        loop {
+            let mut opts = libbpf_sys::bpf_prog_query_opts {
+                sz: size_of::<libbpf_sys::bpf_prog_query_opts>() as _,
+                ..Default::default()
+            };
+            if !prog_ids.is_empty() {
+                opts.prog_ids = prog_ids.as_mut_ptr();
+                opts.link_ids = link_ids.as_mut_ptr();
+            }
+            opts.__bindgen_anon_1.count = prog_ids.len() as u32;
+
+            let ret = unsafe { libbpf_sys::bpf_prog_query_opts(ifindex, attach_type, &mut opts) };
+            // SAFETY: Both union members are plain `u32`.
+            let count = unsafe { opts.__bindgen_anon_1.count } as usize;
+            // More programs may have been attached since we retrieved the
+            // count, in which case we just try again with more space.
+            if ret == -libc::ENOSPC {
+                prog_ids.resize(count, 0);
+                link_ids.resize(count, 0);
+                continue;
+            }
+            let () = util::parse_ret(ret)?;
+
+            if count > prog_ids.len() {
+                // We only learned about the count so far; fetch the ids.
+                prog_ids.resize(count, 0);
+                link_ids.resize(count, 0);
+                continue;
+            }
+            prog_ids.truncate(count);
+            link_ids.truncate(count);
+            break Ok(TcxChain {
+                prog_ids,
+                link_ids,
+                revision: opts.revision,
+                _non_exhaustive: (),
+            });
+        }
+    }
+
+    /// Retrieve information about the loaded program from the kernel,
+    /// including the IDs of the maps it references.
+    ///
+    /// Large variable length data, such as the program's instructions, are
+    /// not included; use [`ProgInfoIter`][crate::query::ProgInfoIter] with
+    /// [`ProgInfoQueryOptions`] to retrieve those for arbitrary programs.
+    pub fn info(&self) -> Result<ProgramInfo> {
+        let opts = ProgInfoQueryOptions::default().include_map_ids(true);
+        ProgramInfo::load_from_fd(self.as_fd(), &opts)
+    }
+
+    /// Retrieve the tag of the loaded program, a hash of its instructions
+    /// as computed by the kernel.
+    ///
+    /// Use [`Tag`][crate::query::Tag] to format it the way `bpftool` does:
+    /// `Tag(prog.tag()?).to_string()`.
+    pub fn tag(&self) -> Result<[u8; 8]> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.tag.0)
+    }
+
+    /// Retrieve the machine code the kernel JIT compiled the program into.
+    ///
+    /// The returned vector is empty if the program has not been JIT
+    /// compiled, e.g., because the JIT is disabled, or if the caller lacks
+    /// the privileges to dump it.
+    pub fn jited_insns(&self) -> Result<Vec<u8>> {
+        let opts = ProgInfoQueryOptions::default().include_jited_prog_insns(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        Ok(info.jited_prog_insns)
+    }
+
+    /// Retrieve the lengths, in bytes, of the JIT compiled functions making
+    /// up the program.
+    ///
+    /// A program with subprograms is JIT compiled into one function per
+    /// subprogram, which [`Program::jited_insns`] reports concatenated. The
+    /// lengths returned here, in the same order, allow for splitting said
+    /// machine code into the individual functions. Lengths are reported as
+    /// zero if the program has not been JIT compiled or if the caller lacks
+    /// the privileges to dump it.
+    pub fn jited_func_lens(&self) -> Result<Vec<u32>> {
+        let opts = ProgInfoQueryOptions::default().include_jited_func_lens(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        Ok(info.jited_func_lens)
+    }
+
+    /// Retrieve the kernel addresses of the JIT compiled functions making up
+    /// the program.
+    ///
+    /// Addresses are reported in the same order as the lengths returned by
+    /// [`Program::jited_func_lens`] and correspond to the `bpf_prog_*`
+    /// entries in `/proc/kallsyms`, allowing for the symbolization of
+    /// addresses in BPF code. Addresses are reported as zero if the program
+    /// has not been JIT compiled or if they are hidden from the caller, e.g.,
+    /// because of `kernel.kptr_restrict` or because of missing privileges.
+    pub fn jited_ksyms(&self) -> Result<Vec<u64>> {
+        let opts = ProgInfoQueryOptions::default().include_jited_ksyms(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        let ksyms = info
+            .jited_ksyms
+            .into_iter()
+            .map(|addr| addr as usize as u64)
+            .collect();
+        Ok(ksyms)
+    }
+
+    /// Retrieve the program's instructions as translated by the kernel.
+    ///
+    /// In contrast to [`Program::insns`], which returns libbpf's copy of the
+    /// instructions as they were handed to the kernel, this function returns
+    /// the instructions as rewritten by the verifier, e.g., with helper calls
+    /// and map references patched. The caller may need `CAP_BPF` or
+    /// `CAP_SYS_ADMIN` to retrieve them; the returned vector is empty if it
+    /// lacks the privileges to do so.
+    pub fn xlated_insns(&self) -> Result<Vec<libbpf_sys::bpf_insn>> {
+        let opts = ProgInfoQueryOptions::default().include_xlated_prog_insns(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        let insns = info
+            .xlated_prog_insns
+            .chunks_exact(size_of::<libbpf_sys::bpf_insn>())
+            .map(|chunk| unsafe {
+                // SAFETY: `chunk` is exactly as large as a `bpf_insn`, which
+                //         is valid for any bit pattern.
+                ptr::read_unaligned(chunk.as_ptr().cast::<libbpf_sys::bpf_insn>())
+            })
+            .collect();
+        Ok(insns)
+    }
+
+    /// Retrieve the total time, in nanoseconds, the program has spent
+    /// running.
+    ///
+    /// Run time is only accounted while statistics collection is enabled,
+    /// e.g., via [`enable_stats`][crate::enable_stats].
+    pub fn run_time_ns(&self) -> Result<u64> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.run_time_ns)
+    }
+
+    /// Retrieve the number of times the program has been run.
+    ///
+    /// Runs are only counted while statistics collection is enabled, e.g.,
+    /// via [`enable_stats`][crate::enable_stats].
+    pub fn run_cnt(&self) -> Result<u64> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.run_cnt)
+    }
+
+    /// Retrieve the number of times an invocation of the program was
+    /// skipped because of recursion prevention, e.g., because a tracing
+    /// program triggered itself or another invocation was already running
+    /// on the same CPU.
+    ///
+    /// In contrast to [`Program::run_cnt`], these are counted regardless of
+    /// whether statistics collection is enabled.
+    pub fn recursion_misses(&self) -> Result<u64> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.recursion_misses)
+    }
+
+    /// Retrieve the IDs of the maps the program references.
+    ///
+    /// Use [`MapHandle::from_map_id`][crate::MapHandle::from_map_id] to open
+    /// any of the maps.
+    pub fn map_ids(&self) -> Result<Vec<u32>> {
+        let opts = ProgInfoQueryOptions::default().include_map_ids(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        Ok(info.map_ids)
+    }
+
+    /// Retrieve the ID of the BTF object associated with the program.
+    ///
+    /// An ID of zero indicates that the program has no BTF associated.
+    pub fn btf_id(&self) -> Result<u32> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.btf_id)
+    }
+
+    /// Retrieve the function information records of the program, mapping
+    /// instruction offsets to BTF function types.
+    ///
+    /// The referenced type IDs are relative to the program's BTF, as
+    /// identified by [`Program::btf_id`].
+    pub fn func_info(&self) -> Result<Vec<libbpf_sys::bpf_func_info>> {
+        let opts = ProgInfoQueryOptions::default().include_func_info(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        Ok(info.func_info)
+    }
+
+    /// Retrieve the line information records of the program, mapping
+    /// instruction offsets to source lines.
+    ///
+    /// File names and source lines are provided as offsets into the string
+    /// section of the program's BTF, as identified by [`Program::btf_id`],
+    /// which is necessary to resolve them.
+    pub fn line_info(&self) -> Result<Vec<LineInfo>> {
+        let opts = ProgInfoQueryOptions::default().include_line_info(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        Ok(info.line_info)
+    }
+
+    /// Retrieve the line information records of the program with file
+    /// names and source lines resolved.
+    ///
+    /// The program's BTF, as identified by [`Program::btf_id`], is loaded
+    /// from the kernel once for resolving all records. The returned vector
+    /// is empty if the program has no BTF associated.
+    pub fn source_lines(&self) -> Result<Vec<SourceLine>> {
+        let opts = ProgInfoQueryOptions::default().include_line_info(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        if info.btf_id == 0 {
+            return Ok(Vec::new());
+        }
+
+        let btf = Btf::from_id(info.btf_id)?;
+        let resolve = |offset| {
+            btf.name_at(offset)
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let lines = info
+            .line_info
+            .iter()
+            .map(|li| SourceLine {
+                insn_off: li.insn_off,
+                file_name: resolve(li.file_name_off),
+                line: li.line_num,
+                column: li.line_col,
+                src_line: resolve(li.line_off),
+            })
+            .collect();
+        Ok(lines)
+    }
+
+    /// Retrieve the number of instructions the verifier processed while
+    /// verifying the program.
+    ///
+    /// In contrast to [`Program::insn_cnt`], this number accounts for all
+    /// paths explored by the verifier and is what is checked against the
+    /// kernel's complexity limit.
+    pub fn verified_insns(&self) -> Result<u32> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.verified_insns)
+    }
+
+    /// Retrieve the instruction counts of the program at its various
+    /// stages, e.g., to understand why [`OpenProgram::insn_cnt`] and
+    /// [`Program::insn_cnt`] differ from what the kernel reports.
+    pub fn insn_counts(&self) -> Result<InsnCounts> {
+        let opts = ProgInfoQueryOptions::default().include_xlated_prog_insns(true);
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &opts)?;
+        let counts = InsnCounts {
+            libbpf: self.insn_cnt(),
+            verified: info.verified_insns,
+            xlated: info.xlated_prog_insns.len() / size_of::<libbpf_sys::bpf_insn>(),
+            _non_exhaustive: (),
+        };
+        Ok(counts)
+    }
+
+    /// Retrieve the amount of memory, in bytes, the program charges against
+    /// the memlock limit or memory cgroup.
+    ///
+    /// The kernel does not report this number as part of the program
+    /// information, but only through the `fdinfo` of the program's file
+    /// descriptor, which consequently has to be accessible via `procfs`.
+    pub fn memlock_bytes(&self) -> Result<u64> {
+        let path = format!("/proc/self/fdinfo/{}", self.as_fd().as_raw_fd());
+        let fdinfo = fs::read_to_string(path)?;
+        fdinfo
+            .lines()
+            .find_map(|line| line.strip_prefix("memlock:"))
+            .ok_or_else(|| Error::with_invalid_data("fdinfo does not contain memlock"))?
+            .trim()
+            .parse()
+            .map_err(|err| Error::with_invalid_data(format!("invalid memlock value: {err}")))
+    }
+
+    /// Retrieve the time the program was loaded at, relative to system boot.
+    pub fn load_time_since_boot(&self) -> Result<Duration> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.load_time)
     }
 
-    /// Returns program id by fd
-    pub fn get_id_by_fd(fd: BorrowedFd<'_>) -> Result<u32> {
-        let mut prog_info = libbpf_sys::bpf_prog_info::default();
-        let prog_info_ptr: *mut libbpf_sys::bpf_prog_info = &mut prog_info;
-        let mut len = size_of::<libbpf_sys::bpf_prog_info>() as u32;
-        let ret = unsafe {
-            libbpf_sys::bpf_obj_get_info_by_fd(
-                fd.as_raw_fd(),
-                prog_info_ptr as *mut c_void,
-                &mut len,
-            )
+    /// Retrieve the wall clock time the program was loaded at.
+    ///
+    /// The kernel reports the load time relative to system boot, which is
+    /// converted based on the current `CLOCK_BOOTTIME`. Please use
+    /// [`Program::load_time_since_boot`] for the unconverted value.
+    pub fn load_time(&self) -> Result<SystemTime> {
+        let since_boot = self.load_time_since_boot()?;
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
         };
-        util::parse_ret(ret)?;
-        Ok(prog_info.id)
+        let rc = unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut ts) };
+        if rc != 0 {
+            return Err(Error::from(io::Error::last_os_error()));
+        }
+        let uptime = Duration::new(ts.tv_sec as _, ts.tv_nsec as _);
+        SystemTime::now()
+            .checked_sub(uptime)
+            .and_then(|boot_time| boot_time.checked_add(since_boot))
+            .ok_or_else(|| Error::with_invalid_data("program load time is out of range"))
+    }
+
+    /// Retrieve the ID of the user that loaded the program.
+    pub fn created_by_uid(&self) -> Result<u32> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.created_by_uid)
+    }
+
+    /// Check whether the program has a GPL compatible license and may
+    /// consequently use GPL-only helpers.
+    pub fn is_gpl_compatible(&self) -> Result<bool> {
+        let info = ProgramInfo::load_from_fd(self.as_fd(), &ProgInfoQueryOptions::default())?;
+        Ok(info.gpl_compatible)
     }
 
     /// Returns flags that have been set for the program.
@@ -505,11 +3151,23 @@ impl Program {
         }
     }
 
+    /// Retrieve the kernel's name for the attach type of the program, e.g.,
+    /// `"cgroup_inet_ingress"`. See [`ProgramAttachType::name`].
+    pub fn attach_type_name(&self) -> &'static str {
+        self.attach_type().name()
+    }
+
     /// Return `true` if the bpf program is set to autoload, `false` otherwise.
     pub fn autoload(&self) -> bool {
         unsafe { libbpf_sys::bpf_program__autoload(self.ptr.as_ptr()) }
     }
 
+    /// Return `true` if the bpf program is set to be automatically attached,
+    /// `false` otherwise.
+    pub fn autoattach(&self) -> bool {
+        unsafe { libbpf_sys::bpf_program__autoattach(self.ptr.as_ptr()) }
+    }
+
     /// Return the bpf program's log level.
     pub fn log_level(&self) -> u32 {
         unsafe { libbpf_sys::bpf_program__log_level(self.ptr.as_ptr()) }
@@ -517,25 +3175,118 @@ impl Program {
 
     /// [Pin](https://facebookmicrosites.github.io/bpf/blog/2018/08/31/object-lifetime.html#bpffs)
     /// this program to bpffs.
+    ///
+    /// A program may be pinned to multiple paths, all of which are reported
+    /// by [`Program::pinned_paths`].
     pub fn pin<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        let path_c = util::path_to_cstring(path)?;
+        let path_c = util::path_to_cstring(&path)?;
         let path_ptr = path_c.as_ptr();
 
         let ret = unsafe { libbpf_sys::bpf_program__pin(self.ptr.as_ptr(), path_ptr) };
-        util::parse_ret(ret)
+        let () = util::parse_ret(ret)?;
+        self.pin_paths.push(path.as_ref().to_path_buf());
+        Ok(())
+    }
+
+    /// [Pin](https://facebookmicrosites.github.io/bpf/blog/2018/08/31/object-lifetime.html#bpffs)
+    /// this program to bpffs and set the permission bits of the created file
+    /// to `mode`.
+    ///
+    /// The kernel creates pins readable and writable by their owner only.
+    /// Anybody with access to a pin can use [`bpf_obj_get`][libbpf_sys::bpf_obj_get]
+    /// on it and gets a file descriptor referring to the program, keeping it
+    /// alive and, depending on their privileges, allowing them to attach or
+    /// run it. Only loosen permissions when all users granted access are
+    /// trusted with the program.
+    ///
+    /// If the permissions can't be changed, the program is unpinned again.
+    pub fn pin_with_mode<P: AsRef<Path>>(&mut self, path: P, mode: u32) -> Result<()> {
+        let () = self.pin(&path)?;
+        let result = fs::set_permissions(&path, Permissions::from_mode(mode));
+        if let Err(err) = result {
+            let _ = self.unpin(&path);
+            return Err(Error::from(err)).with_context(|| {
+                format!(
+                    "failed to set mode of pinned program {}",
+                    path.as_ref().display()
+                )
+            });
+        }
+        Ok(())
     }
 
     /// [Unpin](https://facebookmicrosites.github.io/bpf/blog/2018/08/31/object-lifetime.html#bpffs)
     /// this program from bpffs
     pub fn unpin<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        let path_c = util::path_to_cstring(path)?;
+        let path_c = util::path_to_cstring(&path)?;
         let path_ptr = path_c.as_ptr();
 
         let ret = unsafe { libbpf_sys::bpf_program__unpin(self.ptr.as_ptr(), path_ptr) };
-        util::parse_ret(ret)
+        let () = util::parse_ret(ret)?;
+        self.pin_paths.retain(|pin_path| pin_path != path.as_ref());
+        Ok(())
+    }
+
+    /// Unpin this program from `path`, if it is pinned there.
+    ///
+    /// Contrary to [`Program::unpin`], it is not an error if nothing is
+    /// pinned at `path`. Before removing the pin, it is checked that it
+    /// actually refers to this program; if anything else is pinned at
+    /// `path`, an error is returned and the pin is left untouched.
+    pub fn try_unpin<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let fd = match Self::get_fd_by_pinned_path(path) {
+            Ok(fd) => fd,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.pin_paths.retain(|pin_path| pin_path != path);
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
+        if Self::get_id_by_fd(fd.as_fd())? != Self::get_id_by_fd(self.as_fd())? {
+            return Err(Error::with_invalid_data(format!(
+                "{} does not refer to program `{}`",
+                path.display(),
+                self.name
+            )));
+        }
+        self.unpin(path)
+    }
+
+    /// Returns whether the program has been pinned via [`Program::pin`]
+    /// (and not since been unpinned from all such paths).
+    pub fn is_pinned(&self) -> bool {
+        !self.pin_paths.is_empty()
+    }
+
+    /// Returns the most recent path the program was pinned to via
+    /// [`Program::pin`] and that it is still pinned to, if it is pinned,
+    /// otherwise, None is returned.
+    ///
+    /// libbpf does not track pin paths of programs, so pins created by other
+    /// means (e.g., by another process) are not reflected.
+    pub fn get_pin_path(&self) -> Option<&Path> {
+        self.pin_paths.last().map(PathBuf::as_path)
+    }
+
+    /// Returns all paths the program is pinned to via [`Program::pin`], in
+    /// the order they were pinned.
+    ///
+    /// libbpf pins programs at exactly the path provided, which is reported
+    /// as is. Similar to [`Program::get_pin_path`], pins created by other
+    /// means are not reflected.
+    pub fn pinned_paths(&self) -> &[PathBuf] {
+        &self.pin_paths
     }
 
     /// Auto-attach based on prog section
+    ///
+    /// Not all sections carry enough information for auto-attachment, e.g.,
+    /// a plain `SEC("kprobe")` lacks the function to attach to. An error of
+    /// kind [`ErrorKind::Unsupported`] is reported for those, in which case
+    /// one of the explicit `attach_*` methods, e.g.,
+    /// [`Program::attach_kprobe`], has to be used instead.
     pub fn attach(&mut self) -> Result<Link> {
         util::create_bpf_entity_checked(|| unsafe {
             libbpf_sys::bpf_program__attach(self.ptr.as_ptr())
@@ -544,11 +3295,31 @@ impl Program {
             // SAFETY: the pointer came from libbpf and has been checked for errors
             Link::new(ptr)
         })
+        .map_err(|err| match err.kind() {
+            ErrorKind::Unsupported => err.context(format!(
+                "section `{}` of program `{}` does not support auto-attachment; use one of the \
+                 explicit `Program::attach_*` methods instead",
+                self.section, self.name
+            )),
+            _ => err,
+        })
     }
 
     /// Attach this program to a
     /// [cgroup](https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html).
-    pub fn attach_cgroup(&mut self, cgroup_fd: i32) -> Result<Link> {
+    pub fn attach_cgroup(&mut self, cgroup_fd: BorrowedFd<'_>) -> Result<Link> {
+        self.attach_cgroup_impl(cgroup_fd.as_raw_fd())
+    }
+
+    /// Attach this program to a
+    /// [cgroup](https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html),
+    /// identified by a raw file descriptor.
+    #[deprecated(note = "use `Program::attach_cgroup` with a `BorrowedFd` instead")]
+    pub fn attach_cgroup_raw(&mut self, cgroup_fd: i32) -> Result<Link> {
+        self.attach_cgroup_impl(cgroup_fd)
+    }
+
+    fn attach_cgroup_impl(&mut self, cgroup_fd: i32) -> Result<Link> {
         util::create_bpf_entity_checked(|| unsafe {
             libbpf_sys::bpf_program__attach_cgroup(self.ptr.as_ptr(), cgroup_fd)
         })
@@ -558,10 +3329,21 @@ impl Program {
         })
     }
 
-    /// Attach this program to a [perf event](https://linux.die.net/man/2/perf_event_open).
-    pub fn attach_perf_event(&mut self, pfd: i32) -> Result<Link> {
+    /// Attach this program to an
+    /// [LSM](https://en.wikipedia.org/wiki/Linux_Security_Modules) hook scoped
+    /// to the given
+    /// [cgroup](https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html).
+    ///
+    /// The program has to be loaded with the
+    /// [`ProgramAttachType::LsmCgroup`] attach type, e.g., by being defined
+    /// with `SEC("lsm_cgroup")`.
+    pub fn attach_lsm_cgroup(&mut self, cgroup_fd: BorrowedFd<'_>) -> Result<Link> {
+        if !matches!(self.attach_type(), ProgramAttachType::LsmCgroup) {
+            return Err(Error::from_raw_os_error(libc::EINVAL));
+        }
+
         util::create_bpf_entity_checked(|| unsafe {
-            libbpf_sys::bpf_program__attach_perf_event(self.ptr.as_ptr(), pfd)
+            libbpf_sys::bpf_program__attach_cgroup(self.ptr.as_ptr(), cgroup_fd.as_raw_fd())
         })
         .map(|ptr| unsafe {
             // SAFETY: the pointer came from libbpf and has been checked for errors
@@ -569,6 +3351,161 @@ impl Program {
         })
     }
 
+    /// Attach this program to a
+    /// [cgroup](https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html)
+    /// using the legacy `BPF_PROG_ATTACH` API, providing additional options.
+    ///
+    /// In contrast to [`Program::attach_cgroup`], which creates a BPF link
+    /// that is detached once the returned [`Link`] is dropped and that always
+    /// co-exists with other links on the same hook, an attachment created by
+    /// this method is not tied to the lifetime of any object and persists
+    /// until it is explicitly detached. The attach type used is the
+    /// program's expected attach type.
+    pub fn attach_cgroup_with_opts(
+        &self,
+        cgroup_fd: BorrowedFd<'_>,
+        opts: CgroupAttachOpts,
+    ) -> Result<()> {
+        let opts = libbpf_sys::bpf_prog_attach_opts::from(opts);
+
+        let ret = unsafe {
+            libbpf_sys::bpf_prog_attach_opts(
+                self.as_fd().as_raw_fd(),
+                cgroup_fd.as_raw_fd(),
+                self.attach_type() as u32,
+                &opts,
+            )
+        };
+        util::parse_ret(ret)
+    }
+
+    /// Detach this program from a
+    /// [cgroup](https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html)
+    /// it got attached to without a link, e.g., via
+    /// [`Program::attach_cgroup_with_opts`].
+    ///
+    /// `attach_type` has to match the program's expected attach type. An
+    /// error of kind [`ErrorKind::NotFound`] is reported if the program is
+    /// not attached to the cgroup.
+    pub fn detach_cgroup(
+        &self,
+        cgroup_fd: BorrowedFd<'_>,
+        attach_type: ProgramAttachType,
+    ) -> Result<()> {
+        let attach_type_raw = attach_type.clone() as u32;
+        if attach_type_raw != self.attach_type() as u32 {
+            return Err(Error::with_invalid_data(format!(
+                "attach type ({attach_type}) does not match program's expected attach type ({})",
+                self.attach_type(),
+            )));
+        }
+
+        let ret = unsafe {
+            libbpf_sys::bpf_prog_detach2(
+                self.as_fd().as_raw_fd(),
+                cgroup_fd.as_raw_fd(),
+                attach_type_raw,
+            )
+        };
+        util::parse_ret(ret)
+    }
+
+    /// Attach this program to a [perf event](https://linux.die.net/man/2/perf_event_open).
+    ///
+    /// Ownership of `pfd` passes to the returned [`Link`], which closes the
+    /// perf event once dropped.
+    pub fn attach_perf_event(&mut self, pfd: OwnedFd) -> Result<Link> {
+        self.attach_perf_event_with_opts(pfd, PerfEventOpts::default())
+    }
+
+    /// Attach this program to a [perf event](https://linux.die.net/man/2/perf_event_open),
+    /// identified by a raw file descriptor.
+    ///
+    /// On success, the file descriptor is consumed: it is closed once the
+    /// returned [`Link`] is dropped.
+    #[deprecated(note = "use `Program::attach_perf_event` with an `OwnedFd` instead")]
+    pub fn attach_perf_event_raw(&mut self, pfd: i32) -> Result<Link> {
+        self.attach_perf_event_impl(pfd, PerfEventOpts::default())
+    }
+
+    /// Attach this program to a [perf event](https://linux.die.net/man/2/perf_event_open),
+    /// providing additional options.
+    ///
+    /// Ownership of `pfd` passes to the returned [`Link`], which closes the
+    /// perf event once dropped.
+    pub fn attach_perf_event_with_opts(
+        &mut self,
+        pfd: OwnedFd,
+        opts: PerfEventOpts,
+    ) -> Result<Link> {
+        let link = self.attach_perf_event_impl(pfd.as_raw_fd(), opts)?;
+        // libbpf takes ownership of the perf event file descriptor on
+        // successful attachment and closes it when the link is destroyed.
+        let _fd = pfd.into_raw_fd();
+        Ok(link)
+    }
+
+    fn attach_perf_event_impl(&mut self, pfd: i32, opts: PerfEventOpts) -> Result<Link> {
+        let cookie = opts.cookie;
+        let opts = libbpf_sys::bpf_perf_event_opts::from(opts);
+        util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::bpf_program__attach_perf_event_opts(self.ptr.as_ptr(), pfd, &opts)
+        })
+        .map(|ptr| unsafe {
+            // SAFETY: the pointer came from libbpf and has been checked for errors
+            Link::new_with_cookie(ptr, cookie)
+        })
+    }
+
+    /// Open the [perf event](https://linux.die.net/man/2/perf_event_open)
+    /// described by `config` and attach this program to it.
+    ///
+    /// The perf event is closed once the returned [`Link`] is dropped.
+    pub fn attach_perf_event_config(&mut self, config: PerfEventConfig) -> Result<Link> {
+        let pfd = config.open()?;
+        self.attach_perf_event_with_opts(pfd, PerfEventOpts::default())
+    }
+
+    /// Open one perf event as described by `config` on each online CPU and
+    /// attach this program to each of them, with the CPU's ID as cookie.
+    ///
+    /// The program can tell the CPU an event belongs to by means of
+    /// `bpf_get_attach_cookie`, as can the user via [`Link::cookie`]. The
+    /// `cpu` member of `config` is ignored. This is the typical setup of
+    /// CPU sampling profilers, which usually set `pid` to `-1`.
+    ///
+    /// The online CPUs are read from `/sys/devices/system/cpu/online`; an
+    /// error is returned if that fails. Otherwise, the result of attaching
+    /// on each CPU is reported, in ascending order of CPU IDs, so that
+    /// failures on individual CPUs don't prevent attaching on the others.
+    pub fn attach_perf_event_per_cpu(
+        &mut self,
+        config: PerfEventConfig,
+    ) -> Result<Vec<Result<Link>>> {
+        let cpus = util::online_cpus().context("failed to determine online CPUs")?;
+        let links = cpus
+            .into_iter()
+            .map(|cpu| {
+                let config = PerfEventConfig {
+                    cpu: cpu as _,
+                    ..config.clone()
+                };
+                self.attach_perf_event_cpu(config, cpu)
+                    .with_context(|| format!("failed to attach to perf event on CPU {cpu}"))
+            })
+            .collect();
+        Ok(links)
+    }
+
+    fn attach_perf_event_cpu(&mut self, config: PerfEventConfig, cpu: u32) -> Result<Link> {
+        let pfd = config.open()?;
+        let opts = PerfEventOpts {
+            cookie: cpu.into(),
+            ..Default::default()
+        };
+        self.attach_perf_event_with_opts(pfd, opts)
+    }
+
     /// Attach this program to a [userspace
     /// probe](https://www.kernel.org/doc/html/latest/trace/uprobetracer.html).
     pub fn attach_uprobe<T: AsRef<Path>>(
@@ -595,6 +3532,20 @@ impl Program {
         })
     }
 
+    /// Attach this program to a [userspace return
+    /// probe](https://www.kernel.org/doc/html/latest/trace/uprobetracer.html),
+    /// invoked when the function at `func_offset` returns.
+    ///
+    /// This is equivalent to `attach_uprobe(true, pid, binary_path, func_offset)`.
+    pub fn attach_uretprobe<T: AsRef<Path>>(
+        &mut self,
+        pid: i32,
+        binary_path: T,
+        func_offset: usize,
+    ) -> Result<Link> {
+        self.attach_uprobe(true, pid, binary_path, func_offset)
+    }
+
     /// Attach this program to a [userspace
     /// probe](https://www.kernel.org/doc/html/latest/trace/uprobetracer.html),
     /// providing additional options.
@@ -612,16 +3563,24 @@ impl Program {
             cookie,
             retprobe,
             func_name,
+            attach_mode,
             _non_exhaustive,
         } = opts;
 
-        let func_name = util::str_to_cstring(&func_name)?;
+        // An empty function name means that `func_offset` is relative to
+        // the start of the binary.
+        let func_name = if func_name.is_empty() {
+            None
+        } else {
+            Some(util::str_to_cstring(&func_name)?)
+        };
         let opts = libbpf_sys::bpf_uprobe_opts {
             sz: size_of::<libbpf_sys::bpf_uprobe_opts>() as _,
             ref_ctr_offset: ref_ctr_offset as libbpf_sys::size_t,
             bpf_cookie: cookie,
             retprobe,
-            func_name: func_name.as_ptr(),
+            func_name: func_name.as_ref().map_or(ptr::null(), |name| name.as_ptr()),
+            attach_mode: attach_mode as libbpf_sys::probe_attach_mode,
             ..Default::default()
         };
 
@@ -634,19 +3593,323 @@ impl Program {
                 &opts as *const _,
             )
         })
+        .map(|ptr| unsafe {
+            // SAFETY: the pointer came from libbpf and has been checked for errors
+            Link::new_with_cookie(ptr, cookie)
+        })
+        .with_context(|| format!("failed to attach uprobe ({})", attach_mode.describe()))
+    }
+
+    /// Attach this program to the function `symbol_name` in `binary_path`
+    /// via a [userspace
+    /// probe](https://www.kernel.org/doc/html/latest/trace/uprobetracer.html),
+    /// letting libbpf resolve the symbol's file offset.
+    ///
+    /// `symbol_name` can be an unqualified (`"malloc"`) or library-qualified
+    /// (`"malloc@GLIBC_2.2.5"`) name. Functions in shared libraries require
+    /// `binary_path` to refer to the library. An error of kind
+    /// [`ErrorKind::NotFound`][crate::ErrorKind::NotFound] is reported if the
+    /// symbol cannot be found.
+    pub fn attach_uprobe_symbol(
+        &mut self,
+        pid: i32,
+        binary_path: impl AsRef<Path>,
+        symbol_name: impl AsRef<str>,
+        retprobe: bool,
+    ) -> Result<Link> {
+        let binary_path = binary_path.as_ref();
+        let symbol_name = symbol_name.as_ref();
+        let opts = UprobeOpts {
+            retprobe,
+            func_name: symbol_name.to_string(),
+            ..Default::default()
+        };
+        self.attach_uprobe_with_opts(pid, binary_path, 0, opts)
+            .with_context(|| {
+                format!(
+                    "failed to attach uprobe to symbol `{symbol_name}` in `{}`",
+                    binary_path.display()
+                )
+            })
+    }
+
+    /// Attach this program to the functions at each of `offsets` within
+    /// `binary_path`, as per [`Program::attach_uprobe`].
+    ///
+    /// If the program was loaded with the
+    /// [`ProgramAttachType::TraceUprobeMulti`] attach type, all probes are
+    /// attached via [`Program::attach_uprobe_multi`], resulting in a single
+    /// link. Otherwise one link per offset is created, in order.
+    ///
+    /// Attachment is all or nothing: should attaching to any of the offsets
+    /// fail, the links created up to this point are destroyed again and the
+    /// error is returned.
+    pub fn attach_uprobes(
+        &mut self,
+        pid: i32,
+        binary_path: impl AsRef<Path>,
+        offsets: &[usize],
+        retprobe: bool,
+    ) -> Result<Vec<Link>> {
+        let binary_path = binary_path.as_ref();
+        if offsets.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if matches!(self.attach_type(), ProgramAttachType::TraceUprobeMulti) {
+            let opts = UprobeMultiOpts {
+                offsets: offsets.to_vec(),
+                retprobe,
+                ..Default::default()
+            };
+            let link = self.attach_uprobe_multi(pid, binary_path, opts)?;
+            return Ok(vec![link]);
+        }
+
+        offsets
+            .iter()
+            .map(|offset| {
+                self.attach_uprobe(retprobe, pid, binary_path, *offset)
+                    .with_context(|| {
+                        format!(
+                            "failed to attach uprobe to offset {offset:#x} in `{}`",
+                            binary_path.display()
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Attach this program to multiple [userspace
+    /// probes](https://www.kernel.org/doc/html/latest/trace/uprobetracer.html)
+    /// within a single binary at once, using a single link.
+    ///
+    /// The program has to be loaded with the
+    /// [`ProgramAttachType::TraceUprobeMulti`] attach type, e.g., by being
+    /// defined with `SEC("uprobe.multi")`.
+    pub fn attach_uprobe_multi(
+        &mut self,
+        pid: i32,
+        binary_path: impl AsRef<Path>,
+        opts: UprobeMultiOpts,
+    ) -> Result<Link> {
+        let path = util::path_to_cstring(binary_path)?;
+        let path_ptr = path.as_ptr();
+        let UprobeMultiOpts {
+            symbols,
+            offsets,
+            pattern,
+            ref_ctr_offsets,
+            cookies,
+            retprobe,
+            _non_exhaustive,
+        } = opts;
+
+        if !symbols.is_empty() && !offsets.is_empty() {
+            return Err(Error::with_invalid_data(
+                "only one of symbols and offsets may be provided",
+            ));
+        }
+        let cnt = symbols.len().max(offsets.len());
+        if !ref_ctr_offsets.is_empty() && ref_ctr_offsets.len() != cnt {
+            return Err(Error::with_invalid_data(format!(
+                "number of reference counter offsets ({}) does not match number of attach points ({cnt})",
+                ref_ctr_offsets.len()
+            )));
+        }
+        if !cookies.is_empty() && cookies.len() != cnt {
+            return Err(Error::with_invalid_data(format!(
+                "number of cookies ({}) does not match number of attach points ({cnt})",
+                cookies.len()
+            )));
+        }
+
+        // NB: we must hold onto the `CString`s otherwise the pointers in
+        //     `syms` dangle
+        let symbols = symbols
+            .iter()
+            .map(|sym| util::str_to_cstring(sym))
+            .collect::<Result<Vec<_>>>()?;
+        let syms = symbols.iter().map(|sym| sym.as_ptr()).collect::<Vec<_>>();
+        let offsets = offsets
+            .iter()
+            .map(|offset| *offset as c_ulong)
+            .collect::<Vec<_>>();
+        let ref_ctr_offsets = ref_ctr_offsets
+            .iter()
+            .map(|offset| *offset as c_ulong)
+            .collect::<Vec<_>>();
+        let pattern = pattern.as_deref().map(util::str_to_cstring).transpose()?;
+        let pattern_ptr = pattern
+            .as_ref()
+            .map(|pattern| pattern.as_ptr())
+            .unwrap_or_else(ptr::null);
+
+        let opts = libbpf_sys::bpf_uprobe_multi_opts {
+            sz: size_of::<libbpf_sys::bpf_uprobe_multi_opts>() as _,
+            syms: if syms.is_empty() {
+                ptr::null_mut()
+            } else {
+                syms.as_ptr() as *mut _
+            },
+            offsets: if offsets.is_empty() {
+                ptr::null()
+            } else {
+                offsets.as_ptr()
+            },
+            ref_ctr_offsets: if ref_ctr_offsets.is_empty() {
+                ptr::null()
+            } else {
+                ref_ctr_offsets.as_ptr()
+            },
+            cookies: if cookies.is_empty() {
+                ptr::null()
+            } else {
+                cookies.as_ptr()
+            },
+            cnt: cnt as libbpf_sys::size_t,
+            retprobe,
+            ..Default::default()
+        };
+
+        util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::bpf_program__attach_uprobe_multi(
+                self.ptr.as_ptr(),
+                pid,
+                path_ptr,
+                pattern_ptr,
+                &opts as *const _,
+            )
+        })
+        .map(|ptr| unsafe {
+            // SAFETY: the pointer came from libbpf and has been checked for errors
+            Link::new(ptr)
+        })
+    }
+
+    /// Attach this program to a [kernel
+    /// probe](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html).
+    pub fn attach_kprobe<T: AsRef<str>>(&mut self, retprobe: bool, func_name: T) -> Result<Link> {
+        let func_name = util::str_to_cstring(func_name.as_ref())?;
+        let func_name_ptr = func_name.as_ptr();
+        util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::bpf_program__attach_kprobe(self.ptr.as_ptr(), retprobe, func_name_ptr)
+        })
         .map(|ptr| unsafe {
             // SAFETY: the pointer came from libbpf and has been checked for errors
             Link::new(ptr)
         })
     }
 
-    /// Attach this program to a [kernel
-    /// probe](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html).
-    pub fn attach_kprobe<T: AsRef<str>>(&mut self, retprobe: bool, func_name: T) -> Result<Link> {
-        let func_name = util::str_to_cstring(func_name.as_ref())?;
-        let func_name_ptr = func_name.as_ptr();
+    /// Attach this program to a [kernel return
+    /// probe](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html),
+    /// invoked when `func_name` returns.
+    ///
+    /// This is equivalent to `attach_kprobe(true, func_name)`.
+    pub fn attach_kretprobe<T: AsRef<str>>(&mut self, func_name: T) -> Result<Link> {
+        self.attach_kprobe(true, func_name)
+    }
+
+    /// Attach this program to a [kernel
+    /// probe](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html),
+    /// providing additional options.
+    pub fn attach_kprobe_with_opts<T: AsRef<str>>(
+        &mut self,
+        retprobe: bool,
+        func_name: T,
+        opts: KprobeOpts,
+    ) -> Result<Link> {
+        let func_name = util::str_to_cstring(func_name.as_ref())?;
+        let func_name_ptr = func_name.as_ptr();
+        let cookie = opts.cookie;
+        let attach_mode = opts.attach_mode;
+        let opts = libbpf_sys::bpf_kprobe_opts {
+            retprobe,
+            ..libbpf_sys::bpf_kprobe_opts::from(opts)
+        };
+
+        util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::bpf_program__attach_kprobe_opts(
+                self.ptr.as_ptr(),
+                func_name_ptr,
+                &opts as *const _,
+            )
+        })
+        .map(|ptr| unsafe {
+            // SAFETY: the pointer came from libbpf and has been checked for errors
+            Link::new_with_cookie(ptr, cookie)
+        })
+        .with_context(|| format!("failed to attach kprobe ({})", attach_mode.describe()))
+    }
+
+    fn attach_kprobe_multi_impl(&mut self, opts: KprobeMultiOpts) -> Result<Link> {
+        let KprobeMultiOpts {
+            symbols,
+            addresses,
+            pattern,
+            cookies,
+            retprobe,
+            _non_exhaustive,
+        } = opts;
+
+        if !symbols.is_empty() && !addresses.is_empty() {
+            return Err(Error::with_invalid_data(
+                "only one of symbols and addresses may be provided",
+            ));
+        }
+        let cnt = symbols.len().max(addresses.len());
+        if !cookies.is_empty() && cookies.len() != cnt {
+            return Err(Error::with_invalid_data(format!(
+                "number of cookies ({}) does not match number of attach points ({cnt})",
+                cookies.len()
+            )));
+        }
+
+        // NB: we must hold onto the `CString`s otherwise the pointers in
+        //     `syms` dangle
+        let symbols = symbols
+            .iter()
+            .map(|sym| util::str_to_cstring(sym))
+            .collect::<Result<Vec<_>>>()?;
+        let syms = symbols.iter().map(|sym| sym.as_ptr()).collect::<Vec<_>>();
+        let addrs = addresses
+            .iter()
+            .map(|addr| *addr as c_ulong)
+            .collect::<Vec<_>>();
+        let pattern = pattern.as_deref().map(util::str_to_cstring).transpose()?;
+        let pattern_ptr = pattern
+            .as_ref()
+            .map(|pattern| pattern.as_ptr())
+            .unwrap_or_else(ptr::null);
+
+        let opts = libbpf_sys::bpf_kprobe_multi_opts {
+            sz: size_of::<libbpf_sys::bpf_kprobe_multi_opts>() as _,
+            syms: if syms.is_empty() {
+                ptr::null_mut()
+            } else {
+                syms.as_ptr() as *mut _
+            },
+            addrs: if addrs.is_empty() {
+                ptr::null()
+            } else {
+                addrs.as_ptr()
+            },
+            cookies: if cookies.is_empty() {
+                ptr::null()
+            } else {
+                cookies.as_ptr()
+            },
+            cnt: cnt as libbpf_sys::size_t,
+            retprobe,
+            ..Default::default()
+        };
+
         util::create_bpf_entity_checked(|| unsafe {
-            libbpf_sys::bpf_program__attach_kprobe(self.ptr.as_ptr(), retprobe, func_name_ptr)
+            libbpf_sys::bpf_program__attach_kprobe_multi_opts(
+                self.ptr.as_ptr(),
+                pattern_ptr,
+                &opts as *const _,
+            )
         })
         .map(|ptr| unsafe {
             // SAFETY: the pointer came from libbpf and has been checked for errors
@@ -654,16 +3917,58 @@ impl Program {
         })
     }
 
+    /// Attach this program to multiple [kernel
+    /// probes](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html)
+    /// at once, using a single link.
+    ///
+    /// The program has to be loaded with the
+    /// [`ProgramAttachType::TraceKprobeMulti`] attach type, e.g., by being
+    /// defined with `SEC("kprobe.multi")`.
+    pub fn attach_kprobe_multi<T: AsRef<str>>(
+        &mut self,
+        retprobe: bool,
+        symbols: impl IntoIterator<Item = T>,
+    ) -> Result<Link> {
+        let symbols = symbols
+            .into_iter()
+            .map(|sym| sym.as_ref().to_string())
+            .collect();
+        let opts = KprobeMultiOpts {
+            symbols,
+            retprobe,
+            ..Default::default()
+        };
+        self.attach_kprobe_multi_impl(opts)
+    }
+
+    /// Attach this program to multiple [kernel
+    /// probes](https://www.kernel.org/doc/html/latest/trace/kprobetrace.html)
+    /// at once, providing additional options.
+    pub fn attach_kprobe_multi_with_opts(&mut self, opts: KprobeMultiOpts) -> Result<Link> {
+        self.attach_kprobe_multi_impl(opts)
+    }
+
     /// Attach this program to the specified syscall
     pub fn attach_ksyscall<T: AsRef<str>>(
         &mut self,
         retprobe: bool,
         syscall_name: T,
     ) -> Result<Link> {
+        self.attach_ksyscall_with_opts(retprobe, syscall_name, KsyscallOpts::default())
+    }
+
+    /// Attach this program to the specified syscall, providing additional
+    /// options.
+    pub fn attach_ksyscall_with_opts<T: AsRef<str>>(
+        &mut self,
+        retprobe: bool,
+        syscall_name: T,
+        opts: KsyscallOpts,
+    ) -> Result<Link> {
+        let cookie = opts.cookie;
         let opts = libbpf_sys::bpf_ksyscall_opts {
-            sz: size_of::<libbpf_sys::bpf_ksyscall_opts>() as _,
             retprobe,
-            ..Default::default()
+            ..libbpf_sys::bpf_ksyscall_opts::from(opts)
         };
 
         let syscall_name = util::str_to_cstring(syscall_name.as_ref())?;
@@ -673,7 +3978,7 @@ impl Program {
         })
         .map(|ptr| unsafe {
             // SAFETY: the pointer came from libbpf and has been checked for errors
-            Link::new(ptr)
+            Link::new_with_cookie(ptr, cookie)
         })
     }
 
@@ -688,6 +3993,7 @@ impl Program {
         let tp_name = util::str_to_cstring(tp_name)?;
         let tp_name_ptr = tp_name.as_ptr();
 
+        let cookie = tp_opts.as_ref().map_or(0, |opts| opts.cookie);
         util::create_bpf_entity_checked(|| {
             if let Some(tp_opts) = tp_opts {
                 let tp_opts = libbpf_sys::bpf_tracepoint_opts::from(tp_opts);
@@ -711,7 +4017,7 @@ impl Program {
         })
         .map(|ptr| unsafe {
             // SAFETY: the pointer came from libbpf and has been checked for errors
-            Link::new(ptr)
+            Link::new_with_cookie(ptr, cookie)
         })
     }
 
@@ -763,9 +4069,52 @@ impl Program {
     }
 
     /// Attach to a [fentry/fexit kernel probe](https://lwn.net/Articles/801479/)
+    ///
+    /// The program is attached to the target it was loaded for, which may
+    /// be a kernel function or a function of another BPF program (see
+    /// [`OpenProgram::set_attach_target_program`]).
     pub fn attach_trace(&mut self) -> Result<Link> {
+        self.attach_trace_with_opts(TraceOpts::default())
+    }
+
+    /// Attach to a [fentry/fexit kernel probe](https://lwn.net/Articles/801479/),
+    /// providing additional options.
+    pub fn attach_trace_with_opts(&mut self, opts: TraceOpts) -> Result<Link> {
+        let cookie = opts.cookie;
+        let opts = libbpf_sys::bpf_trace_opts::from(opts);
+        util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::bpf_program__attach_trace_opts(self.ptr.as_ptr(), &opts)
+        })
+        .map(|ptr| unsafe {
+            // SAFETY: the pointer came from libbpf and has been checked for errors
+            Link::new_with_cookie(ptr, cookie)
+        })
+    }
+
+    /// Attach this extension program, replacing the function `func_name` of
+    /// the already loaded BPF program referred to by `target_fd`.
+    ///
+    /// The program has to be of type [`ProgramType::Ext`], e.g., by being
+    /// defined with `SEC("freplace")`. Because the kernel verifies an
+    /// extension program against the function it replaces, the target has to
+    /// be set via [`OpenProgram::set_attach_target_fd`] *before* the object is
+    /// loaded; the program can then be attached to that target, or to any
+    /// other program with a compatible function, using this method.
+    /// The original function is restored once the returned [`Link`] is
+    /// dropped.
+    pub fn attach_freplace(
+        &mut self,
+        target_fd: BorrowedFd<'_>,
+        func_name: impl AsRef<str>,
+    ) -> Result<Link> {
+        let func_name = util::str_to_cstring(func_name.as_ref())?;
+        let func_name_ptr = func_name.as_ptr();
         util::create_bpf_entity_checked(|| unsafe {
-            libbpf_sys::bpf_program__attach_trace(self.ptr.as_ptr())
+            libbpf_sys::bpf_program__attach_freplace(
+                self.ptr.as_ptr(),
+                target_fd.as_raw_fd(),
+                func_name_ptr,
+            )
         })
         .map(|ptr| unsafe {
             // SAFETY: the pointer came from libbpf and has been checked for errors
@@ -773,7 +4122,31 @@ impl Program {
         })
     }
 
+    /// Register the `struct_ops` described by `map`, which is expected to
+    /// reference this program.
+    ///
+    /// The program has to be of type [`ProgramType::StructOps`], e.g., by
+    /// being defined with `SEC("struct_ops")`. Because registration happens on
+    /// the `struct_ops` map and not on an individual program, this is a
+    /// convenience wrapper around [`Map::attach_struct_ops`].
+    /// The `struct_ops` gets unregistered once the returned [`Link`] is
+    /// dropped.
+    pub fn attach_struct_ops(&mut self, map: &Map) -> Result<Link> {
+        if !matches!(self.prog_type(), ProgramType::StructOps) {
+            return Err(Error::with_invalid_data(format!(
+                "Invalid program type ({}) for attach_struct_ops()",
+                self.prog_type(),
+            )));
+        }
+
+        map.attach_struct_ops()
+    }
+
     /// Attach a verdict/parser to a [sockmap/sockhash](https://lwn.net/Articles/731133/)
+    ///
+    /// The attachment is not tied to the lifetime of any object and persists
+    /// until it is explicitly removed via [`Program::detach_sockmap`] or the
+    /// map is destroyed.
     pub fn attach_sockmap(&self, map_fd: i32) -> Result<()> {
         let err = unsafe {
             libbpf_sys::bpf_prog_attach(
@@ -786,6 +4159,20 @@ impl Program {
         util::parse_ret(err)
     }
 
+    /// Detach a verdict/parser previously attached to a
+    /// [sockmap/sockhash](https://lwn.net/Articles/731133/) via
+    /// [`Program::attach_sockmap`].
+    pub fn detach_sockmap(&self, map_fd: i32) -> Result<()> {
+        let err = unsafe {
+            libbpf_sys::bpf_prog_detach2(
+                self.as_fd().as_raw_fd(),
+                map_fd,
+                self.attach_type() as u32,
+            )
+        };
+        util::parse_ret(err)
+    }
+
     /// Attach this program to [XDP](https://lwn.net/Articles/825998/)
     pub fn attach_xdp(&mut self, ifindex: i32) -> Result<Link> {
         util::create_bpf_entity_checked(|| unsafe {
@@ -797,8 +4184,113 @@ impl Program {
         })
     }
 
+    /// Attach this program, supporting multi-buffer packets, to
+    /// [XDP](https://lwn.net/Articles/825998/).
+    ///
+    /// The program has to be loaded with [`libbpf_sys::BPF_F_XDP_HAS_FRAGS`]
+    /// set, e.g., by being defined with `SEC("xdp.frags")` or via
+    /// [`OpenProgram::set_flags`]; an error of kind
+    /// [`ErrorKind::InvalidData`] is reported otherwise. Such programs can
+    /// be attached to devices with an MTU exceeding the size of a single
+    /// buffer, which the kernel refuses for other programs. Programs
+    /// supporting fragments can equally be attached with
+    /// [`Program::attach_xdp_with_opts`].
+    pub fn attach_xdp_frags(&mut self, ifindex: i32) -> Result<Link> {
+        if self.flags() & libbpf_sys::BPF_F_XDP_HAS_FRAGS == 0 {
+            return Err(Error::with_invalid_data(
+                "program was not loaded with `BPF_F_XDP_HAS_FRAGS`; consider using `SEC(\"xdp.frags\")`",
+            ));
+        }
+        self.attach_xdp(ifindex)
+    }
+
+    /// Attach this program to the
+    /// [TCX](https://lwn.net/Articles/938632/) hook of the given network
+    /// interface.
+    ///
+    /// The program has to be loaded with the attach type corresponding to
+    /// the requested [`TcxAttachPoint`], e.g., by being defined with
+    /// `SEC("tcx/ingress")` or `SEC("tcx/egress")`.
+    pub fn attach_tcx(&mut self, ifindex: i32, opts: TcxOpts) -> Result<Link> {
+        let TcxOpts {
+            attach_point,
+            flags,
+            relative_fd,
+            relative_id,
+            expected_revision,
+            _non_exhaustive,
+        } = opts;
+
+        let attach_type = self.attach_type();
+        let matches = match attach_point {
+            TcxAttachPoint::Ingress => matches!(attach_type, ProgramAttachType::TcxIngress),
+            TcxAttachPoint::Egress => matches!(attach_type, ProgramAttachType::TcxEgress),
+        };
+        if !matches {
+            return Err(Error::with_invalid_data(format!(
+                "program with attach type {attach_type} cannot be attached to TCX {attach_point:?}"
+            )));
+        }
+
+        let opts = libbpf_sys::bpf_tcx_opts {
+            sz: size_of::<libbpf_sys::bpf_tcx_opts>() as _,
+            flags,
+            relative_fd: relative_fd as _,
+            relative_id,
+            expected_revision,
+            ..Default::default()
+        };
+
+        util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::bpf_program__attach_tcx(self.ptr.as_ptr(), ifindex, &opts)
+        })
+        .map(|ptr| unsafe {
+            // SAFETY: the pointer came from libbpf and has been checked for errors
+            Link::new(ptr)
+        })
+    }
+
+    /// Attach this program to [XDP](https://lwn.net/Articles/825998/) using
+    /// the netlink based API, providing additional options.
+    ///
+    /// In contrast to [`Program::attach_xdp`], which creates a BPF link, an
+    /// attachment created by this method persists until it is explicitly
+    /// detached, e.g., via [`Xdp::detach`][crate::Xdp::detach].
+    pub fn attach_xdp_with_opts(&self, ifindex: i32, opts: XdpOpts) -> Result<()> {
+        let XdpOpts {
+            mut flags,
+            old_prog_fd,
+            _non_exhaustive,
+        } = opts;
+
+        let mut opts = libbpf_sys::bpf_xdp_attach_opts {
+            sz: size_of::<libbpf_sys::bpf_xdp_attach_opts>() as _,
+            ..Default::default()
+        };
+        if let Some(old_prog_fd) = old_prog_fd {
+            opts.old_prog_fd = old_prog_fd;
+            flags |= XdpFlags::REPLACE;
+        }
+
+        let ret = unsafe {
+            libbpf_sys::bpf_xdp_attach(ifindex, self.as_fd().as_raw_fd(), flags.bits(), &opts)
+        };
+        util::parse_ret(ret)
+    }
+
     /// Attach this program to [netns-based programs](https://lwn.net/Articles/819618/)
-    pub fn attach_netns(&mut self, netns_fd: i32) -> Result<Link> {
+    pub fn attach_netns(&mut self, netns_fd: BorrowedFd<'_>) -> Result<Link> {
+        self.attach_netns_impl(netns_fd.as_raw_fd())
+    }
+
+    /// Attach this program to [netns-based programs](https://lwn.net/Articles/819618/),
+    /// identifying the network namespace by a raw file descriptor.
+    #[deprecated(note = "use `Program::attach_netns` with a `BorrowedFd` instead")]
+    pub fn attach_netns_raw(&mut self, netns_fd: i32) -> Result<Link> {
+        self.attach_netns_impl(netns_fd)
+    }
+
+    fn attach_netns_impl(&mut self, netns_fd: i32) -> Result<Link> {
         util::create_bpf_entity_checked(|| unsafe {
             libbpf_sys::bpf_program__attach_netns(self.ptr.as_ptr(), netns_fd)
         })
@@ -808,6 +4300,72 @@ impl Program {
         })
     }
 
+    /// Attach this [socket lookup](https://docs.kernel.org/bpf/prog_sk_lookup.html)
+    /// program to the network namespace referenced by `netns_fd`.
+    ///
+    /// The program has to be loaded with the
+    /// [`ProgramAttachType::SkLookup`] attach type, e.g., by being defined
+    /// with `SEC("sk_lookup")`.
+    pub fn attach_sk_lookup(&mut self, netns_fd: BorrowedFd<'_>) -> Result<Link> {
+        let attach_type = self.attach_type();
+        if !matches!(attach_type, ProgramAttachType::SkLookup) {
+            return Err(Error::with_invalid_data(format!(
+                "program with attach type {attach_type} cannot be attached as socket lookup program"
+            )));
+        }
+
+        self.attach_netns_impl(netns_fd.as_raw_fd())
+    }
+
+    /// Attach this [flow dissector](https://docs.kernel.org/bpf/prog_flow_dissector.html)
+    /// program to the network namespace referenced by `netns_fd`.
+    ///
+    /// The program has to be loaded with the
+    /// [`ProgramAttachType::FlowDissector`] attach type, e.g., by being
+    /// defined with `SEC("flow_dissector")`. Only a single flow dissector can
+    /// be attached to a network namespace; an error of kind
+    /// [`ErrorKind::AlreadyExists`] is reported if one is present already.
+    pub fn attach_flow_dissector(&mut self, netns_fd: BorrowedFd<'_>) -> Result<Link> {
+        let attach_type = self.attach_type();
+        if !matches!(attach_type, ProgramAttachType::FlowDissector) {
+            return Err(Error::with_invalid_data(format!(
+                "program with attach type {attach_type} cannot be attached as flow dissector"
+            )));
+        }
+
+        self.attach_netns_impl(netns_fd.as_raw_fd())
+            .map_err(|err| match err.kind() {
+                ErrorKind::AlreadyExists => {
+                    err.context("a flow dissector is already attached to the network namespace")
+                }
+                _ => err,
+            })
+    }
+
+    /// Attach this program to a
+    /// [netfilter](https://www.netfilter.org/) hook.
+    ///
+    /// The program has to be of type [`ProgramType::Netfilter`], e.g., by
+    /// being defined with `SEC("netfilter")`.
+    pub fn attach_netfilter(&mut self, opts: NetfilterOpts) -> Result<Link> {
+        let pf = opts.pf;
+        if ![libc::NFPROTO_IPV4, libc::NFPROTO_IPV6].contains(&(pf as _)) {
+            return Err(Error::with_invalid_data(format!(
+                "unsupported netfilter protocol family ({pf})"
+            )));
+        }
+
+        let opts = libbpf_sys::bpf_netfilter_opts::from(opts);
+
+        util::create_bpf_entity_checked(|| unsafe {
+            libbpf_sys::bpf_program__attach_netfilter(self.ptr.as_ptr(), &opts)
+        })
+        .map(|ptr| unsafe {
+            // SAFETY: the pointer came from libbpf and has been checked for errors
+            Link::new(ptr)
+        })
+    }
+
     fn attach_usdt_impl(
         &mut self,
         pid: i32,
@@ -822,6 +4380,7 @@ impl Program {
         let usdt_provider_ptr = usdt_provider.as_ptr();
         let usdt_name = util::str_to_cstring(usdt_name)?;
         let usdt_name_ptr = usdt_name.as_ptr();
+        let cookie = usdt_opts.as_ref().map_or(0, |opts| opts.cookie);
         let usdt_opts = usdt_opts.map(libbpf_sys::bpf_usdt_opts::from);
         let usdt_opts_ptr = usdt_opts
             .as_ref()
@@ -840,13 +4399,21 @@ impl Program {
         })
         .map(|ptr| unsafe {
             // SAFETY: the pointer came from libbpf and has been checked for errors
-            Link::new(ptr)
+            Link::new_with_cookie(ptr, cookie)
         })
     }
 
     /// Attach this program to a [USDT](https://lwn.net/Articles/753601/) probe
     /// point. The entry point of the program must be defined with
     /// `SEC("usdt")`.
+    ///
+    /// If `pid` is `-1`, the probe is attached in every process, current and
+    /// future, that maps the binary at `binary_path`. `binary_path` can be an
+    /// executable or a shared library, such as `libc.so.6`; in the latter
+    /// case it has to refer to the library itself, as it cannot be resolved
+    /// from the process' memory mappings. Attaching to a shared library with
+    /// a `pid` of `-1` requires kernel support for BPF cookies (Linux 5.15+);
+    /// on older kernels libbpf rejects it with `ENOTSUP`.
     pub fn attach_usdt(
         &mut self,
         pid: i32,
@@ -866,6 +4433,8 @@ impl Program {
     /// Attach this program to a [USDT](https://lwn.net/Articles/753601/) probe
     /// point, providing additional options. The entry point of the program must
     /// be defined with `SEC("usdt")`.
+    ///
+    /// Please refer to [`Program::attach_usdt`] for the semantics of `pid`.
     pub fn attach_usdt_with_opts(
         &mut self,
         pid: i32,
@@ -887,16 +4456,23 @@ impl Program {
     /// [BPF Iterator](https://www.kernel.org/doc/html/latest/bpf/bpf_iterators.html).
     /// The entry point of the program must be defined with `SEC("iter")` or `SEC("iter.s")`.
     pub fn attach_iter(&mut self, map_fd: BorrowedFd<'_>) -> Result<Link> {
-        util::create_bpf_entity_checked(|| unsafe {
-            let mut linkinfo = libbpf_sys::bpf_iter_link_info::default();
-            linkinfo.map.map_fd = map_fd.as_raw_fd() as _;
-            let attach_opt = libbpf_sys::bpf_iter_attach_opts {
-                link_info: &mut linkinfo as *mut libbpf_sys::bpf_iter_link_info,
-                link_info_len: size_of::<libbpf_sys::bpf_iter_link_info>() as _,
-                sz: size_of::<libbpf_sys::bpf_iter_attach_opts>() as _,
-                ..Default::default()
-            };
+        self.attach_iter_with_opts(IterOpts::Map(map_fd))
+    }
+
+    /// Attach this program to a
+    /// [BPF Iterator](https://www.kernel.org/doc/html/latest/bpf/bpf_iterators.html),
+    /// restricting the iterated kernel objects as described by `opts`.
+    /// The entry point of the program must be defined with `SEC("iter")` or `SEC("iter.s")`.
+    pub fn attach_iter_with_opts(&mut self, opts: IterOpts<'_>) -> Result<Link> {
+        let mut linkinfo = libbpf_sys::bpf_iter_link_info::from(opts);
+        let attach_opt = libbpf_sys::bpf_iter_attach_opts {
+            link_info: &mut linkinfo as *mut libbpf_sys::bpf_iter_link_info,
+            link_info_len: size_of::<libbpf_sys::bpf_iter_link_info>() as _,
+            sz: size_of::<libbpf_sys::bpf_iter_attach_opts>() as _,
+            ..Default::default()
+        };
 
+        util::create_bpf_entity_checked(|| unsafe {
             libbpf_sys::bpf_program__attach_iter(
                 self.ptr.as_ptr(),
                 &attach_opt as *const libbpf_sys::bpf_iter_attach_opts,
@@ -908,32 +4484,117 @@ impl Program {
         })
     }
 
-    /// Test run the program with the given input data.
+    /// Attach this program to `target` with the given attach type using the
+    /// generic `BPF_LINK_CREATE` API.
     ///
-    /// This function uses the
-    /// [BPF_PROG_RUN](https://www.kernel.org/doc/html/latest/bpf/bpf_prog_run.html)
-    /// facility.
-    pub fn test_run<'dat>(&mut self, input: Input<'dat>) -> Result<Output<'dat>> {
-        pub(crate) unsafe fn slice_from_array<'t, T>(
-            items: *mut T,
-            num_items: usize,
-        ) -> Option<&'t mut [T]> {
-            if items.is_null() {
-                None
-            } else {
-                Some(unsafe { slice::from_raw_parts_mut(items, num_items) })
-            }
+    /// This is a low level escape hatch for attach types not otherwise
+    /// wrapped by this crate. Depending on `attach_type`, `target` is a file
+    /// descriptor (e.g., of a cgroup, network namespace, or BPF program) or a
+    /// network interface index (e.g., for XDP and TCX). `attach_type` has to
+    /// match the program's expected attach type, which can be set via
+    /// [`OpenProgram::set_attach_type`] before load.
+    ///
+    /// Because libbpf provides no way to wrap an existing link file
+    /// descriptor, the link is returned as an [`OwnedFd`] and not as a
+    /// [`Link`]. The program gets detached once the last file descriptor
+    /// referring to the link is closed.
+    pub fn attach_link_create(
+        &mut self,
+        target: i32,
+        attach_type: ProgramAttachType,
+        flags: u32,
+    ) -> Result<OwnedFd> {
+        let attach_type_raw = attach_type.clone() as u32;
+        if attach_type_raw != self.attach_type() as u32 {
+            return Err(Error::with_invalid_data(format!(
+                "attach type ({attach_type}) does not match program's expected attach type ({})",
+                self.attach_type(),
+            )));
         }
 
+        let opts = libbpf_sys::bpf_link_create_opts {
+            sz: size_of::<libbpf_sys::bpf_link_create_opts>() as _,
+            flags,
+            ..Default::default()
+        };
+        let fd = unsafe {
+            libbpf_sys::bpf_link_create(
+                self.as_fd().as_raw_fd(),
+                target,
+                attach_type_raw,
+                &opts as *const _,
+            )
+        };
+        util::parse_ret_i32(fd).map(|fd| unsafe {
+            // SAFETY
+            // A file descriptor coming from the bpf_link_create function is always suitable for
+            // ownership and can be cleaned up with close.
+            OwnedFd::from_raw_fd(fd)
+        })
+    }
+
+    /// Validate the given test run input and create the corresponding
+    /// `bpf_test_run_opts`, referencing the input's buffers.
+    ///
+    /// Capturing of output data is left to the caller.
+    fn test_run_opts(&self, input: &mut Input<'_>) -> Result<libbpf_sys::bpf_test_run_opts> {
         let Input {
             context_in,
-            mut context_out,
+            ref mut context_out,
             data_in,
-            mut data_out,
+            ref mut data_out,
             cpu,
             flags,
+            repeat,
+            batch_size,
+            xdp_live_frames,
+            capture_data_out,
             _non_exhaustive: (),
-        } = input;
+        } = *input;
+
+        let live_frames = xdp_live_frames || flags & libbpf_sys::BPF_F_TEST_XDP_LIVE_FRAMES != 0;
+        if live_frames {
+            if !matches!(self.prog_type(), ProgramType::Xdp) {
+                return Err(Error::with_invalid_data(
+                    "live frames mode is only supported for XDP programs",
+                ));
+            }
+            if data_in.is_none() {
+                return Err(Error::with_invalid_data(
+                    "live frames mode requires a packet template in `data_in`",
+                ));
+            }
+            if context_out.is_some() || data_out.is_some() || capture_data_out {
+                return Err(Error::with_invalid_data(
+                    "live frames mode does not support output buffers",
+                ));
+            }
+        }
+
+        // Catch context buffers the kernel would reject with a rather
+        // cryptic error: it refuses input contexts with unsupported
+        // (non-zero) trailing bytes and output buffers too small for the
+        // context.
+        if let Some(size) = self.prog_type().context_struct_size() {
+            if let Some(context_in) = context_in {
+                if context_in.iter().skip(size).any(|b| *b != 0) {
+                    return Err(Error::with_invalid_data(format!(
+                        "input context of {} bytes exceeds the {size} byte context of {} programs",
+                        context_in.len(),
+                        self.prog_type()
+                    )));
+                }
+            }
+            if let Some(context_out) = context_out.as_deref() {
+                if context_out.len() < size {
+                    return Err(Error::with_invalid_data(format!(
+                        "output context buffer of {} bytes is smaller than the {size} byte context of {} programs",
+                        context_out.len(),
+                        self.prog_type()
+                    )));
+                }
+            }
+        }
 
         let mut opts = unsafe { mem::zeroed::<libbpf_sys::bpf_test_run_opts>() };
         opts.sz = size_of_val(&opts) as _;
@@ -942,33 +4603,112 @@ impl Program {
             .unwrap_or_else(ptr::null);
         opts.ctx_size_in = context_in.map(|data| data.len() as _).unwrap_or(0);
         opts.ctx_out = context_out
-            .as_mut()
+            .as_deref_mut()
             .map(|data| data.as_mut_ptr().cast())
             .unwrap_or_else(ptr::null_mut);
-        opts.ctx_size_out = context_out.map(|data| data.len() as _).unwrap_or(0);
+        opts.ctx_size_out = context_out
+            .as_deref()
+            .map(|data| data.len() as _)
+            .unwrap_or(0);
         opts.data_in = data_in
             .map(|data| data.as_ptr().cast())
             .unwrap_or_else(ptr::null);
         opts.data_size_in = data_in.map(|data| data.len() as _).unwrap_or(0);
         opts.data_out = data_out
-            .as_mut()
+            .as_deref_mut()
             .map(|data| data.as_mut_ptr().cast())
             .unwrap_or_else(ptr::null_mut);
-        opts.data_size_out = data_out.map(|data| data.len() as _).unwrap_or(0);
+        opts.data_size_out = data_out.as_deref().map(|data| data.len() as _).unwrap_or(0);
         opts.cpu = cpu;
         opts.flags = flags;
+        if xdp_live_frames {
+            opts.flags |= libbpf_sys::BPF_F_TEST_XDP_LIVE_FRAMES;
+        }
+        opts.repeat = repeat as _;
+        opts.batch_size = batch_size;
+        Ok(opts)
+    }
+
+    /// Test run the program with the given input data.
+    ///
+    /// This function uses the
+    /// [BPF_PROG_RUN](https://www.kernel.org/doc/html/latest/bpf/bpf_prog_run.html)
+    /// facility.
+    pub fn test_run<'dat>(&mut self, input: Input<'dat>) -> Result<Output<'dat>> {
+        pub(crate) unsafe fn slice_from_array<'t, T>(
+            items: *mut T,
+            num_items: usize,
+        ) -> Option<&'t mut [T]> {
+            if items.is_null() {
+                None
+            } else {
+                Some(unsafe { slice::from_raw_parts_mut(items, num_items) })
+            }
+        }
+
+        let mut input = input;
+        let mut opts = self.test_run_opts(&mut input)?;
+
+        // Slack on top of the input size for the captured output data. See
+        // `Input::capture_data_out`.
+        const CAPTURE_DATA_OUT_SLACK: usize = 4096;
+        let mut captured_data = (input.capture_data_out && input.data_out.is_none()).then(|| {
+            let len = input.data_in.map(<[u8]>::len).unwrap_or(0);
+            vec![0u8; len + CAPTURE_DATA_OUT_SLACK]
+        });
+        if let Some(data) = captured_data.as_deref_mut() {
+            opts.data_out = data.as_mut_ptr().cast();
+            opts.data_size_out = data.len() as _;
+        }
 
         let rc = unsafe { libbpf_sys::bpf_prog_test_run_opts(self.as_fd().as_raw_fd(), &mut opts) };
         let () = util::parse_ret(rc)?;
         let output = Output {
             return_value: opts.retval,
             context: unsafe { slice_from_array(opts.ctx_out.cast(), opts.ctx_size_out as _) },
-            data: unsafe { slice_from_array(opts.data_out.cast(), opts.data_size_out as _) },
+            // The kernel wrote into our own buffer in case we are capturing;
+            // it is reported via `captured_data` instead.
+            data: if captured_data.is_some() {
+                None
+            } else {
+                unsafe { slice_from_array(opts.data_out.cast(), opts.data_size_out as _) }
+            },
+            duration: Duration::from_nanos(opts.duration.into()),
+            captured_data: captured_data.map(|mut data| {
+                let () = data.truncate(opts.data_size_out as _);
+                data
+            }),
             _non_exhaustive: (),
         };
         Ok(output)
     }
 
+    /// Create a [`TestRunner`] for repeatedly test running the program with
+    /// the given input.
+    ///
+    /// In contrast to [`Program::test_run`], the input is validated and
+    /// prepared once, making each [`TestRunner::run`] cheap. Capturing of
+    /// output data via [`Input::capture_data_out`] is not supported; provide
+    /// a `data_out` buffer instead.
+    pub fn test_runner<'dat>(&self, input: Input<'dat>) -> Result<TestRunner<'_, 'dat>> {
+        let mut input = input;
+        if input.capture_data_out && input.data_out.is_none() {
+            return Err(Error::with_invalid_data(
+                "capturing output data is not supported by `TestRunner`",
+            ));
+        }
+
+        let opts = self.test_run_opts(&mut input)?;
+        let runner = TestRunner {
+            fd: self.as_fd(),
+            ctx_size_out: opts.ctx_size_out,
+            data_size_out: opts.data_size_out,
+            opts,
+            _data: PhantomData,
+        };
+        Ok(runner)
+    }
+
     /// Returns the number of instructions that form the program.
     ///
     /// Please see note in [`OpenProgram::insn_cnt`].
@@ -985,4 +4725,201 @@ impl Program {
         let ptr = unsafe { libbpf_sys::bpf_program__insns(self.ptr.as_ptr()) };
         unsafe { slice::from_raw_parts(ptr, count) }
     }
+
+    /// Disassemble the program's instructions, as returned by
+    /// [`Program::insns`], into text, one line per instruction.
+    ///
+    /// Please see note in [`OpenProgram::disassemble`].
+    pub fn disassemble(&self) -> String {
+        disasm::disassemble(self.insns())
+    }
+
+    /// Decode the program's instructions, as returned by
+    /// [`Program::insns`], for programmatic analysis.
+    ///
+    /// Each item describes one instruction; 64 bit immediate loads, which
+    /// occupy two instruction slots, are reported as a single item.
+    pub fn decoded_insns(&self) -> impl Iterator<Item = DecodedInsn> + '_ {
+        disasm::decode(self.insns())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check that we can parse every program type from its kernel and its
+    /// variant name.
+    #[test]
+    fn program_type_from_str_roundtrip() {
+        for ty in PROGRAM_TYPES {
+            let parsed = ProgramType::from_str(ty.name()).unwrap();
+            assert_eq!(parsed as u32, ty as u32);
+
+            let parsed = ProgramType::from_str(&ty.to_string()).unwrap();
+            assert_eq!(parsed as u32, ty as u32);
+
+            let parsed = ProgramType::from_str(&ty.name().to_uppercase()).unwrap();
+            assert_eq!(parsed as u32, ty as u32);
+        }
+
+        assert_eq!(ProgramType::SchedCls.name(), "sched_cls");
+        assert!(ProgramType::from_str("sched-cls").is_err());
+        assert!(ProgramType::from_str("unknown").is_err());
+    }
+
+    /// Check that we can parse every attach type from its kernel and its
+    /// variant name.
+    #[test]
+    fn program_attach_type_from_str_roundtrip() {
+        for ty in PROGRAM_ATTACH_TYPES {
+            let parsed = ProgramAttachType::from_str(ty.name()).unwrap();
+            assert_eq!(parsed as u32, ty.clone() as u32);
+
+            let parsed = ProgramAttachType::from_str(&ty.to_string()).unwrap();
+            assert_eq!(parsed as u32, ty.clone() as u32);
+
+            let parsed = ProgramAttachType::from_str(&ty.name().to_uppercase()).unwrap();
+            assert_eq!(parsed as u32, ty as u32);
+        }
+
+        assert_eq!(
+            ProgramAttachType::CgroupInetIngress.name(),
+            "cgroup_inet_ingress"
+        );
+        assert!(ProgramAttachType::from_str("cgroup-inet-ingress").is_err());
+        assert!(ProgramAttachType::from_str("unknown").is_err());
+    }
+
+    /// Check that program and attach types can be deserialized from their
+    /// names, including `"unknown"`, and that unknown names are rejected.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn program_types_deserialize() {
+        use serde::de::value::Error as DeError;
+        use serde::de::value::StrDeserializer;
+        use serde::de::IntoDeserializer as _;
+
+        let de = |name: &'static str| -> StrDeserializer<'_, DeError> { name.into_deserializer() };
+
+        for ty in ProgramType::iter().chain([ProgramType::Unknown]) {
+            let parsed = ProgramType::deserialize(de(ty.name())).unwrap();
+            assert_eq!(parsed as u32, ty as u32);
+        }
+        assert!(
+            ProgramType::deserialize::<serde::de::value::StrDeserializer<'_, DeError>>(de(
+                "foobar"
+            ))
+            .is_err()
+        );
+
+        for ty in PROGRAM_ATTACH_TYPES
+            .into_iter()
+            .chain([ProgramAttachType::Unknown])
+        {
+            let parsed = ProgramAttachType::deserialize(de(ty.name())).unwrap();
+            assert_eq!(parsed as u32, ty as u32);
+        }
+        assert!(
+            ProgramAttachType::deserialize::<serde::de::value::StrDeserializer<'_, DeError>>(de(
+                "foobar"
+            ))
+            .is_err()
+        );
+    }
+
+    /// Check that `ProgramType::iter` yields all program types, but not
+    /// `ProgramType::Unknown`.
+    #[test]
+    fn program_type_iter() {
+        let types = ProgramType::iter().collect::<Vec<_>>();
+        assert_eq!(types.len(), ProgramType::Netfilter as usize + 1);
+        for (idx, ty) in types.iter().enumerate() {
+            assert_eq!(*ty as usize, idx);
+        }
+        assert!(!types.iter().any(|ty| matches!(ty, ProgramType::Unknown)));
+    }
+
+    /// Check that we report context struct sizes for the expected program
+    /// types.
+    #[test]
+    fn program_type_context_struct_size() {
+        assert_eq!(ProgramType::SchedCls.context_struct_size(), Some(192));
+        assert_eq!(
+            ProgramType::Xdp.context_struct_size(),
+            Some(size_of::<libbpf_sys::xdp_md>())
+        );
+        assert_eq!(
+            ProgramType::FlowDissector.context_struct_size(),
+            Some(size_of::<libbpf_sys::bpf_flow_keys>())
+        );
+        assert_eq!(ProgramType::Tracing.context_struct_size(), None);
+        assert_eq!(ProgramType::Unknown.context_struct_size(), None);
+    }
+
+    /// Check the categorization of program types.
+    #[test]
+    fn program_type_categories() {
+        assert!(ProgramType::Kprobe.is_tracing());
+        assert!(ProgramType::Tracing.is_tracing());
+        assert!(!ProgramType::Xdp.is_tracing());
+
+        assert!(ProgramType::Xdp.is_networking());
+        assert!(ProgramType::CgroupSkb.is_networking());
+        assert!(!ProgramType::CgroupSysctl.is_networking());
+
+        assert!(ProgramType::CgroupSysctl.is_cgroup());
+        assert!(ProgramType::SockOps.is_cgroup());
+        assert!(!ProgramType::SchedCls.is_cgroup());
+
+        assert!(ProgramType::Lsm.needs_attach_target());
+        assert!(!ProgramType::Kprobe.needs_attach_target());
+
+        // Tracing and networking are disjoint categories.
+        for ty in ProgramType::iter() {
+            assert!(!(ty.is_tracing() && ty.is_networking()), "{ty}");
+        }
+
+        let ty = ProgramType::Unknown;
+        assert!(!ty.is_tracing());
+        assert!(!ty.is_networking());
+        assert!(!ty.is_cgroup());
+        assert!(!ty.needs_attach_target());
+    }
+
+    /// Check that the chained setters of the various attach options are
+    /// reflected in the converted libbpf options.
+    #[test]
+    fn attach_opts_setters() {
+        let opts = KprobeOpts::default()
+            .cookie(7)
+            .offset(4)
+            .attach_mode(ProbeAttachMode::Perf);
+        let opts = libbpf_sys::bpf_kprobe_opts::from(opts);
+        assert_eq!(opts.bpf_cookie, 7);
+        assert_eq!(opts.offset, 4);
+        assert_eq!(opts.attach_mode, libbpf_sys::PROBE_ATTACH_MODE_PERF);
+        assert!(!opts.retprobe);
+
+        let opts = PerfEventOpts::default().cookie(42).force_ioctl_attach(true);
+        let opts = libbpf_sys::bpf_perf_event_opts::from(opts);
+        assert_eq!(opts.bpf_cookie, 42);
+        assert!(opts.force_ioctl_attach);
+
+        let opts = NetfilterOpts::default()
+            .pf(libc::NFPROTO_IPV4 as _)
+            .hooknum(libc::NF_INET_LOCAL_IN as _)
+            .priority(-128);
+        let opts = libbpf_sys::bpf_netfilter_opts::from(opts);
+        assert_eq!(opts.pf, libc::NFPROTO_IPV4 as u32);
+        assert_eq!(opts.hooknum, libc::NF_INET_LOCAL_IN as u32);
+        assert_eq!(opts.priority, -128);
+
+        let opts = UprobeOpts::default().func_name("malloc").retprobe(true);
+        assert_eq!(opts.func_name, "malloc");
+        assert!(opts.retprobe);
+
+        let opts = XdpOpts::default().old_prog_fd(3);
+        assert_eq!(opts.old_prog_fd, Some(3));
+    }
 }
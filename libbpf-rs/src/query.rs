@@ -12,7 +12,11 @@
 
 use std::ffi::c_void;
 use std::ffi::CString;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 use std::io;
+use std::mem;
 use std::mem::size_of_val;
 use std::os::fd::AsFd;
 use std::os::fd::AsRawFd;
@@ -117,12 +121,35 @@ impl From<&libbpf_sys::bpf_line_info> for LineInfo {
     }
 }
 
+/// A [`LineInfo`] record resolved against the BTF it refers to.
+#[derive(Clone, Debug)]
+pub struct SourceLine {
+    /// Offset of instruction in vector
+    pub insn_off: u32,
+    /// Name of the source file
+    pub file_name: String,
+    /// Line number
+    pub line: u32,
+    /// Line column number
+    pub column: u32,
+    /// Contents of the source line
+    pub src_line: String,
+}
+
 /// Bpf identifier tag
 #[derive(Debug, Clone, Default)]
 #[repr(C)]
-pub struct Tag([u8; 8]);
+pub struct Tag(pub [u8; 8]);
+
+impl Display for Tag {
+    /// Format the tag as hex string, the way `bpftool` prints it.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
 
 /// Information about a BPF program
+#[non_exhaustive]
 #[derive(Debug, Clone)]
 // TODO: Document members.
 #[allow(missing_docs)]
@@ -155,6 +182,8 @@ pub struct ProgramInfo {
     pub run_cnt: u64,
     /// Skipped BPF executions due to recursion or concurrent execution prevention.
     pub recursion_misses: u64,
+    /// Number of instructions processed by the verifier.
+    pub verified_insns: u32,
 }
 
 /// An iterator for the information of loaded bpf programs
@@ -269,7 +298,7 @@ impl ProgInfoQueryOptions {
 }
 
 impl ProgramInfo {
-    fn load_from_fd(fd: BorrowedFd<'_>, opts: &ProgInfoQueryOptions) -> Result<Self> {
+    pub(crate) fn load_from_fd(fd: BorrowedFd<'_>, opts: &ProgInfoQueryOptions) -> Result<Self> {
         let mut item = libbpf_sys::bpf_prog_info::default();
 
         let mut xlated_prog_insns: Vec<u8> = Vec::new();
@@ -398,6 +427,7 @@ impl ProgramInfo {
             run_time_ns: item.run_time_ns,
             run_cnt: item.run_cnt,
             recursion_misses: item.recursion_misses,
+            verified_insns: item.verified_insns,
         });
     }
 }
@@ -645,6 +675,21 @@ pub struct LinkInfo {
 }
 
 impl LinkInfo {
+    pub(crate) fn load_from_fd(fd: BorrowedFd<'_>) -> Result<Self> {
+        // Padding bytes need to be zero as well; see `gen_info_impl`.
+        let mut item: libbpf_sys::bpf_link_info = unsafe { mem::zeroed() };
+        let item_ptr: *mut libbpf_sys::bpf_link_info = &mut item;
+        let mut len = size_of_val(&item) as u32;
+
+        let ret = unsafe {
+            libbpf_sys::bpf_obj_get_info_by_fd(fd.as_raw_fd(), item_ptr as *mut c_void, &mut len)
+        };
+        util::parse_ret(ret)?;
+
+        // SANITY: `from_uapi` never fails.
+        Ok(Self::from_uapi(fd, item).unwrap())
+    }
+
     fn from_uapi(fd: BorrowedFd<'_>, mut s: libbpf_sys::bpf_link_info) -> Option<Self> {
         let type_info = match s.type_ {
             libbpf_sys::BPF_LINK_TYPE_RAW_TRACEPOINT => {
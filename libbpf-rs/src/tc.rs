@@ -33,6 +33,140 @@ pub const TC_H_MAJ_MASK: u32 = 0xFFFF0000;
 #[allow(missing_docs)]
 pub const TC_H_MIN_MASK: u32 = 0x0000FFFF;
 
+/// The size of `struct __sk_buff`, as used by the kernel's UAPI.
+pub(crate) const SK_BUFF_SIZE: usize = 192;
+
+/// The context of a program operating on socket buffers, i.e., `struct
+/// __sk_buff`, as used for
+/// [`ProgramInput::context_in`][crate::ProgramInput::context_in] when test
+/// running, e.g., a TC, socket filter, or `cgroup_skb` program via
+/// [`Program::test_run`][crate::Program::test_run].
+///
+/// Only the members the kernel honors as input are provided. Most notably,
+/// the length of the packet as well as its protocol are derived from the
+/// input data, and providing them is rejected by the kernel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SkBuffContext {
+    /// The mark of the packet.
+    pub mark: u32,
+    /// The queueing priority of the packet.
+    pub priority: u32,
+    /// The index of the interface the packet is received on.
+    pub ingress_ifindex: u32,
+    /// The index of the interface the packet is processed on. If greater
+    /// than `1` (the loopback device), the interface has to exist.
+    pub ifindex: u32,
+    /// Control buffer, free for use by programs.
+    pub cb: [u32; 5],
+    /// The time stamp of the packet.
+    pub tstamp: u64,
+    /// The length of the packet on the wire, or `0` for the length of the
+    /// input data.
+    pub wire_len: u32,
+    /// The number of GSO segments.
+    pub gso_segs: u32,
+    /// The size of GSO segments.
+    pub gso_size: u32,
+    /// The hardware time stamp of the packet.
+    pub hwtstamp: u64,
+}
+
+impl SkBuffContext {
+    /// Create an empty context, to be filled in by the chained setters.
+    ///
+    /// ```no_run
+    /// # use libbpf_rs::SkBuffContext;
+    /// let ctx = SkBuffContext::new().mark(42).cb([1, 2, 3, 4, 5]).to_bytes();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set [`Self::mark`].
+    pub fn mark(mut self, mark: u32) -> Self {
+        self.mark = mark;
+        self
+    }
+
+    /// Set [`Self::priority`].
+    pub fn priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set [`Self::ingress_ifindex`].
+    pub fn ingress_ifindex(mut self, ingress_ifindex: u32) -> Self {
+        self.ingress_ifindex = ingress_ifindex;
+        self
+    }
+
+    /// Set [`Self::ifindex`].
+    pub fn ifindex(mut self, ifindex: u32) -> Self {
+        self.ifindex = ifindex;
+        self
+    }
+
+    /// Set [`Self::cb`].
+    pub fn cb(mut self, cb: [u32; 5]) -> Self {
+        self.cb = cb;
+        self
+    }
+
+    /// Set [`Self::tstamp`].
+    pub fn tstamp(mut self, tstamp: u64) -> Self {
+        self.tstamp = tstamp;
+        self
+    }
+
+    /// Set [`Self::wire_len`].
+    pub fn wire_len(mut self, wire_len: u32) -> Self {
+        self.wire_len = wire_len;
+        self
+    }
+
+    /// Set [`Self::gso_segs`].
+    pub fn gso_segs(mut self, gso_segs: u32) -> Self {
+        self.gso_segs = gso_segs;
+        self
+    }
+
+    /// Set [`Self::gso_size`].
+    pub fn gso_size(mut self, gso_size: u32) -> Self {
+        self.gso_size = gso_size;
+        self
+    }
+
+    /// Set [`Self::hwtstamp`].
+    pub fn hwtstamp(mut self, hwtstamp: u64) -> Self {
+        self.hwtstamp = hwtstamp;
+        self
+    }
+
+    /// Convert the context into the bytes of a `struct __sk_buff`.
+    pub fn to_bytes(&self) -> [u8; SK_BUFF_SIZE] {
+        fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+            buf[offset..offset + bytes.len()].copy_from_slice(bytes)
+        }
+
+        // libbpf-sys does not provide bindings for `struct __sk_buff`, so
+        // we lay it out manually, using the offsets of the UAPI definition.
+        let mut buf = [0; SK_BUFF_SIZE];
+        put(&mut buf, 8, &self.mark.to_ne_bytes());
+        put(&mut buf, 32, &self.priority.to_ne_bytes());
+        put(&mut buf, 36, &self.ingress_ifindex.to_ne_bytes());
+        put(&mut buf, 40, &self.ifindex.to_ne_bytes());
+        for (idx, cb) in self.cb.iter().enumerate() {
+            put(&mut buf, 48 + idx * size_of::<u32>(), &cb.to_ne_bytes());
+        }
+        put(&mut buf, 152, &self.tstamp.to_ne_bytes());
+        put(&mut buf, 160, &self.wire_len.to_ne_bytes());
+        put(&mut buf, 164, &self.gso_segs.to_ne_bytes());
+        put(&mut buf, 176, &self.gso_size.to_ne_bytes());
+        put(&mut buf, 184, &self.hwtstamp.to_ne_bytes());
+        buf
+    }
+}
+
 /// Represents a location where a TC-BPF filter can be attached.
 ///
 /// The BPF TC subsystem has different control paths from other BPF programs.
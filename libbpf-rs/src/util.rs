@@ -1,9 +1,12 @@
 use std::any::type_name;
 use std::ffi::CStr;
 use std::ffi::CString;
+use std::fs;
 use std::io;
 use std::mem::transmute;
 use std::ops::Deref;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
 use std::os::raw::c_char;
 use std::path::Path;
 use std::ptr::NonNull;
@@ -63,6 +66,50 @@ pub fn num_possible_cpus() -> Result<usize> {
     parse_ret(ret).map(|()| ret as usize)
 }
 
+/// Parse a CPU list in the kernel's format, e.g., `0-3,5,7-8`.
+pub(crate) fn parse_cpu_list(list: &str) -> Result<Vec<u32>> {
+    let invalid = || Error::with_invalid_data(format!("invalid CPU list `{list}`"));
+    let list = list.trim();
+    if list.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut cpus = Vec::new();
+    for range in list.split(',') {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let start = start.parse::<u32>().map_err(|_| invalid())?;
+        let end = end.parse::<u32>().map_err(|_| invalid())?;
+        if start > end {
+            return Err(invalid());
+        }
+        cpus.extend(start..=end);
+    }
+    Ok(cpus)
+}
+
+/// Get the IDs of the CPUs currently online.
+pub(crate) fn online_cpus() -> Result<Vec<u32>> {
+    const ONLINE_CPUS: &str = "/sys/devices/system/cpu/online";
+
+    let list = fs::read_to_string(ONLINE_CPUS)?;
+    parse_cpu_list(&list)
+}
+
+/// Enable collection of BPF program run time statistics, i.e., the
+/// `run_time_ns` and `run_cnt` reported for each program, system wide.
+///
+/// Statistics are collected for as long as the returned file descriptor (or
+/// any other one enabling them) is open.
+pub fn enable_stats() -> Result<OwnedFd> {
+    let fd = unsafe { libbpf_sys::bpf_enable_stats(libbpf_sys::BPF_STATS_RUN_TIME) };
+    parse_ret_i32(fd).map(|fd| unsafe {
+        // SAFETY
+        // A file descriptor coming from the bpf_enable_stats function is always suitable for
+        // ownership and can be cleaned up with close.
+        OwnedFd::from_raw_fd(fd)
+    })
+}
+
 pub fn parse_ret(ret: i32) -> Result<()> {
     if ret < 0 {
         // Error code is returned negative, flip to positive to match errno
@@ -162,6 +209,27 @@ mod tests {
         assert!(num > 0);
     }
 
+    /// Check that we can parse CPU lists as reported by the kernel.
+    #[test]
+    fn cpu_list_parsing() {
+        assert_eq!(parse_cpu_list("").unwrap(), Vec::<u32>::new());
+        assert_eq!(parse_cpu_list("0\n").unwrap(), vec![0]);
+        assert_eq!(parse_cpu_list("0-3").unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(parse_cpu_list("0-1,4,6-7").unwrap(), vec![0, 1, 4, 6, 7]);
+
+        assert!(parse_cpu_list("3-1").is_err());
+        assert!(parse_cpu_list("0,,1").is_err());
+        assert!(parse_cpu_list("a-b").is_err());
+    }
+
+    /// Check that we can retrieve the online CPUs.
+    #[test]
+    fn online_cpu_retrieval() {
+        let cpus = online_cpus().unwrap();
+        assert!(!cpus.is_empty());
+        assert!(cpus.len() <= num_possible_cpus().unwrap());
+    }
+
     /// Check that we can convert a `[c_char]` into a `CStr`.
     #[test]
     fn c_char_slice_conversion() {
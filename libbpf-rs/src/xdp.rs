@@ -1,5 +1,6 @@
 use bitflags::bitflags;
 use std::mem::size_of;
+use std::mem::transmute;
 use std::os::unix::io::AsRawFd;
 use std::os::unix::io::BorrowedFd;
 
@@ -8,6 +9,7 @@ use crate::Result;
 
 bitflags! {
     /// Flags to configure the `XDP` operations
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
     pub struct XdpFlags: u32 {
         /// No flags.
         const NONE              = 0;
@@ -29,6 +31,50 @@ bitflags! {
 
 }
 
+/// The context of an XDP program, i.e., `struct xdp_md`, as used for
+/// [`ProgramInput::context_in`][crate::ProgramInput::context_in] when test
+/// running an XDP program via [`Program::test_run`][crate::Program::test_run].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct XdpContext {
+    /// The index of the interface the packet is received on. The interface
+    /// has to exist.
+    pub ingress_ifindex: u32,
+    /// The index of the receive queue of the interface the packet is
+    /// received on.
+    pub rx_queue_index: u32,
+    /// The length of the metadata at the start of the input data, preceding
+    /// the actual packet.
+    pub meta_len: u32,
+}
+
+impl XdpContext {
+    /// Create a new context for a packet received on the interface with
+    /// index `ingress_ifindex`, on receive queue `rx_queue_index`.
+    pub fn new(ingress_ifindex: u32, rx_queue_index: u32) -> Self {
+        Self {
+            ingress_ifindex,
+            rx_queue_index,
+            meta_len: 0,
+        }
+    }
+
+    /// Convert the context into the bytes of a `struct xdp_md`, for input
+    /// data (including metadata) of `data_len` bytes.
+    pub fn to_bytes(&self, data_len: u32) -> [u8; size_of::<libbpf_sys::xdp_md>()] {
+        let xdp_md = libbpf_sys::xdp_md {
+            data: self.meta_len,
+            data_end: data_len,
+            data_meta: 0,
+            ingress_ifindex: self.ingress_ifindex,
+            rx_queue_index: self.rx_queue_index,
+            egress_ifindex: 0,
+        };
+        // SAFETY: `xdp_md` is plain old data consisting only of `u32`
+        //         members, without any padding.
+        unsafe { transmute::<libbpf_sys::xdp_md, [u8; size_of::<libbpf_sys::xdp_md>()]>(xdp_md) }
+    }
+}
+
 /// Represents a XDP program.
 ///
 /// This struct exposes operations to attach, detach and query a XDP program
@@ -1,22 +1,31 @@
 #![allow(clippy::let_unit_value)]
 #![warn(clippy::absolute_paths)]
 
+use std::arch::asm;
 use std::collections::HashSet;
 use std::env::current_exe;
 use std::ffi::c_int;
 use std::ffi::c_void;
+use std::ffi::CStr;
 use std::fs;
 use std::hint;
 use std::io;
 use std::io::Read;
 use std::mem::size_of;
+use std::os::unix::fs::PermissionsExt as _;
 use std::os::unix::io::AsFd;
+use std::os::unix::io::FromRawFd;
+use std::os::unix::io::OwnedFd;
 use std::path::Path;
 use std::path::PathBuf;
 use std::ptr::addr_of;
 use std::slice;
+use std::sync::atomic::AtomicU16;
+use std::sync::atomic::Ordering;
 use std::sync::mpsc::channel;
+use std::thread;
 use std::time::Duration;
+use std::time::SystemTime;
 
 use plain::Plain;
 use probe::probe;
@@ -24,9 +33,21 @@ use scopeguard::defer;
 use std::ptr;
 use tempfile::NamedTempFile;
 
+use libbpf_rs::__internal_skel::ObjectSkeletonConfigBuilder;
+use libbpf_rs::enable_stats;
 use libbpf_rs::num_possible_cpus;
+use libbpf_rs::query::LinkTypeInfo;
+use libbpf_rs::query::ProgInfoIter;
+use libbpf_rs::query::Tag;
 use libbpf_rs::AsRawLibbpf;
+use libbpf_rs::ErrorKind;
+use libbpf_rs::InsnClass;
 use libbpf_rs::Iter;
+use libbpf_rs::IterOpts;
+use libbpf_rs::KprobeMultiOpts;
+use libbpf_rs::KprobeOpts;
+use libbpf_rs::KsyscallOpts;
+use libbpf_rs::Link;
 use libbpf_rs::Linker;
 use libbpf_rs::Map;
 use libbpf_rs::MapFlags;
@@ -36,10 +57,16 @@ use libbpf_rs::MapType;
 use libbpf_rs::Object;
 use libbpf_rs::ObjectBuilder;
 use libbpf_rs::OpenObject;
+use libbpf_rs::PerfEventConfig;
+use libbpf_rs::PerfEventOpts;
+use libbpf_rs::PerfEventSampling;
+use libbpf_rs::ProbeAttachMode;
 use libbpf_rs::Program;
+use libbpf_rs::ProgramAttachType;
 use libbpf_rs::ProgramInput;
 use libbpf_rs::ProgramType;
 use libbpf_rs::TracepointOpts;
+use libbpf_rs::UprobeMultiOpts;
 use libbpf_rs::UprobeOpts;
 use libbpf_rs::UsdtOpts;
 
@@ -716,6 +743,94 @@ fn test_sudo_object_loading_loaded_map_from_id() {
     );
 }
 
+/// Check that we can retrieve the verifier log of a successfully loaded
+/// program.
+#[test]
+fn test_sudo_object_program_log_buf() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("runqslower.bpf.o");
+    let prog = open_obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+    let () = prog.set_log_buf(1 << 20).expect("failed to set log buffer");
+    let () = prog.set_log_level(1).expect("failed to set log level");
+
+    let obj = open_obj.load().expect("failed to load object");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+    let log = prog.log_buf().expect("no log buffer set");
+    assert!(log.contains("processed"), "{log}");
+
+    let prog = obj
+        .prog("handle__sched_switch")
+        .expect("failed to find program");
+    assert!(prog.log_buf().is_none());
+}
+
+/// Check that we can load an object while capturing a program's verifier
+/// log.
+#[test]
+fn test_sudo_object_load_with_log() {
+    bump_rlimit_mlock();
+
+    let open_obj = open_test_object("runqslower.bpf.o");
+    let err = open_obj.load_with_log("asdf", 1).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let mut open_obj = open_test_object("runqslower.bpf.o");
+    let prog = open_obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+    assert_eq!(prog.log_level(), 0);
+    let () = prog.set_log_level(2).expect("failed to set log level");
+    assert_eq!(prog.log_level(), 2);
+
+    let (obj, log) = open_obj
+        .load_with_log("handle__sched_wakeup", 1)
+        .expect("failed to load object");
+    assert!(log.contains("processed"), "{log}");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+    assert_eq!(prog.log_level(), 1);
+    assert!(prog.log_buf().is_none());
+
+    let mut open_obj = open_test_object("tracepoint.bpf.o");
+    let prog = open_obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("failed to find program");
+    // `bpf_get_attach_cookie` is not available to socket filters.
+    prog.set_prog_type(ProgramType::SocketFilter);
+
+    let err = open_obj
+        .load_with_log("handle__tracepoint_with_cookie", 1)
+        .unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("verifier log"), "{msg}");
+    assert!(msg.contains("bpf_get_attach_cookie"), "{msg}");
+}
+
+/// Check that the verifier log is reported when a program fails to load.
+#[test]
+fn test_sudo_object_load_failure_verifier_log() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("tracepoint.bpf.o");
+    let prog = open_obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("failed to find program");
+    // `bpf_get_attach_cookie` is not available to socket filters, which
+    // causes the verifier to reject the program.
+    prog.set_prog_type(ProgramType::SocketFilter);
+
+    let err = open_obj.load().unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("verifier log"), "{msg}");
+    assert!(msg.contains("bpf_get_attach_cookie"), "{msg}");
+}
+
 #[test]
 fn test_sudo_object_programs() {
     bump_rlimit_mlock();
@@ -738,6 +853,394 @@ fn test_sudo_object_programs_iter_mut() {
     assert!(obj.progs_iter().count() == 3);
 }
 
+/// Check that we can retrieve the kernel's names of a program's type and
+/// attach type.
+#[test]
+fn test_sudo_object_program_type_names() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog("xdp_filter").expect("failed to find program");
+    assert_eq!(prog.prog_type_name(), "xdp");
+    assert_eq!(prog.attach_type_name(), "xdp");
+}
+
+/// Check that we can retrieve information about a loaded program.
+#[test]
+fn test_sudo_object_program_info() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let info = prog.info().expect("failed to get program info");
+    assert_eq!(info.id, Program::get_id_by_fd(prog.as_fd()).unwrap());
+    // The kernel truncates program names to 15 characters.
+    assert_eq!(info.name.to_str().unwrap(), "handle__sched_w");
+    assert!(matches!(info.ty, ProgramType::Tracing));
+    assert!(!info.map_ids.is_empty());
+
+    let info_by_id = Program::info_by_id(info.id).expect("failed to get program info by id");
+    assert_eq!(info_by_id.id, info.id);
+    assert_eq!(info_by_id.name, info.name);
+    assert_eq!(info_by_id.map_ids, info.map_ids);
+}
+
+/// Check that we can retrieve and format a loaded program's tag.
+#[test]
+fn test_sudo_object_program_tag() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let tag = prog.tag().expect("failed to get program tag");
+    assert_ne!(tag, [0; 8]);
+    assert_eq!(prog.info().unwrap().tag.0, tag);
+
+    let hex = Tag(tag).to_string();
+    assert_eq!(hex.len(), 16);
+    assert!(hex.chars().all(|c| c.is_ascii_hexdigit()));
+}
+
+/// Check that we can retrieve a loaded program's JIT compiled machine code.
+#[test]
+fn test_sudo_object_program_jited_insns() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let jit_enabled = fs::read_to_string("/proc/sys/net/core/bpf_jit_enable")
+        .map(|s| s.trim() != "0")
+        .unwrap_or(false);
+    let insns = prog
+        .jited_insns()
+        .expect("failed to get JITed instructions");
+    assert_eq!(!insns.is_empty(), jit_enabled);
+}
+
+/// Check that the lengths of a loaded program's JIT compiled functions
+/// add up to its machine code.
+#[test]
+fn test_sudo_object_program_jited_func_lens() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let insns = prog
+        .jited_insns()
+        .expect("failed to get JITed instructions");
+    let func_lens = prog
+        .jited_func_lens()
+        .expect("failed to get JITed function lengths");
+    let total = func_lens.iter().map(|len| *len as usize).sum::<usize>();
+    assert_eq!(total, insns.len());
+}
+
+/// Check that we can retrieve the kernel addresses of a loaded program's
+/// JIT compiled functions.
+#[test]
+fn test_sudo_object_program_jited_ksyms() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let func_lens = prog
+        .jited_func_lens()
+        .expect("failed to get JITed function lengths");
+    let ksyms = prog
+        .jited_ksyms()
+        .expect("failed to get JITed kernel symbols");
+    assert_eq!(ksyms.len(), func_lens.len());
+}
+
+/// Check that we can retrieve a loaded program's translated instructions.
+#[test]
+fn test_sudo_object_program_xlated_insns() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let insns = prog
+        .xlated_insns()
+        .expect("failed to get translated instructions");
+    assert!(!insns.is_empty());
+    // The last instruction of a program always is an exit.
+    assert_eq!(
+        insns.last().unwrap().code,
+        (libbpf_sys::BPF_JMP | libbpf_sys::BPF_EXIT) as u8
+    );
+}
+
+/// Check that we can walk from a loaded program to the maps it references.
+#[test]
+fn test_sudo_object_program_map_ids() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+    let map = obj.map("start").expect("failed to find map");
+    let map_id = map.info().expect("failed to get map info").info.id;
+
+    let map_ids = prog.map_ids().expect("failed to get map ids");
+    assert!(map_ids.contains(&map_id));
+
+    let handle = MapHandle::from_map_id(map_id).expect("failed to open map");
+    assert_eq!(handle.name(), map.name());
+
+    // A program not referencing any maps reports none.
+    let obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog("xdp_filter").expect("failed to find program");
+    assert_eq!(prog.map_ids().unwrap(), Vec::<u32>::new());
+}
+
+/// Check that we can retrieve a loaded program's BTF function and line
+/// information.
+#[test]
+fn test_sudo_object_program_btf_info() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    assert_ne!(prog.btf_id().expect("failed to get BTF id"), 0);
+
+    let func_info = prog.func_info().expect("failed to get function info");
+    assert!(!func_info.is_empty());
+    assert_eq!(func_info[0].insn_off, 0);
+
+    let line_info = prog.line_info().expect("failed to get line info");
+    assert!(!line_info.is_empty());
+    assert!(line_info.iter().any(|li| li.line_num > 0));
+}
+
+/// Check that we can resolve a loaded program's line information into
+/// source lines.
+#[test]
+fn test_sudo_object_program_source_lines() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let lines = prog.source_lines().expect("failed to get source lines");
+    assert_eq!(
+        lines.len(),
+        prog.line_info().expect("failed to get line info").len()
+    );
+    assert!(lines
+        .iter()
+        .any(|line| line.file_name.ends_with("runqslower.bpf.c")));
+    assert!(lines.iter().any(|line| line.line > 0));
+}
+
+/// Check that we can retrieve the number of instructions the verifier
+/// processed.
+#[test]
+fn test_sudo_object_program_verified_insns() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let verified_insns = prog
+        .verified_insns()
+        .expect("failed to get verified instruction count");
+    assert!(verified_insns > 0);
+}
+
+/// Check that we can retrieve a loaded program's instruction counts at its
+/// various stages.
+#[test]
+fn test_sudo_object_program_insn_counts() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let counts = prog
+        .insn_counts()
+        .expect("failed to get instruction counts");
+    assert_eq!(counts.libbpf, prog.insn_cnt());
+    assert_eq!(counts.verified, prog.verified_insns().unwrap());
+    assert_eq!(counts.xlated, prog.xlated_insns().unwrap().len());
+    assert!(counts.xlated > 0);
+}
+
+/// Check that we can retrieve the memory accounted to a loaded program.
+#[test]
+fn test_sudo_object_program_memlock_bytes() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let memlock = prog.memlock_bytes().expect("failed to get memlock bytes");
+    assert!(memlock > 0);
+}
+
+/// Check that we can retrieve the time a program was loaded at.
+#[test]
+fn test_sudo_object_program_load_time() {
+    bump_rlimit_mlock();
+
+    let before = SystemTime::now();
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let since_boot = prog
+        .load_time_since_boot()
+        .expect("failed to get load time");
+    assert_eq!(since_boot, prog.info().unwrap().load_time);
+
+    let load_time = prog.load_time().expect("failed to get load time");
+    // Allow for some slack, as the conversion is not exact.
+    let slack = Duration::from_secs(1);
+    assert!(load_time + slack >= before);
+    assert!(load_time <= SystemTime::now() + slack);
+}
+
+/// Check that we can retrieve the user that loaded a program and whether it
+/// is GPL compatible.
+#[test]
+fn test_sudo_object_program_owner_and_license() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let uid = unsafe { libc::getuid() };
+    assert_eq!(prog.created_by_uid().unwrap(), uid);
+    assert!(prog.is_gpl_compatible().unwrap());
+}
+
+/// Check that a duplicated program file descriptor refers to the same
+/// program and keeps it loaded.
+#[test]
+fn test_sudo_object_program_fd_dup() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog("handle__sched_wakeup")
+        .expect("failed to find program");
+    let id = Program::get_id_by_fd(prog.as_fd()).unwrap();
+
+    let fd = prog
+        .as_fd()
+        .try_clone_to_owned()
+        .expect("failed to duplicate fd");
+    assert_eq!(Program::get_id_by_fd(fd.as_fd()).unwrap(), id);
+
+    drop(obj);
+    assert_eq!(Program::get_id_by_fd(fd.as_fd()).unwrap(), id);
+}
+
+/// Check that we can look up a loaded program by its name.
+#[test]
+fn test_sudo_object_program_get_fd_by_name() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("runqslower.bpf.o");
+    let _prog = obj
+        .prog("handle__sched_switch")
+        .expect("failed to find program");
+
+    // The name is longer than what the kernel stores and gets truncated.
+    let fd = Program::get_fd_by_name("handle__sched_switch").expect("failed to find program");
+    let id = Program::get_id_by_fd(fd.as_fd()).unwrap();
+    let info = ProgInfoIter::default()
+        .find(|info| info.id == id)
+        .expect("failed to find program info");
+    assert_eq!(info.name.to_str().unwrap(), "handle__sched_s");
+
+    let err = Program::get_fd_by_name("does_not_exist").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+/// Check that we can unload an individual program of an object.
+#[test]
+fn test_sudo_object_unload_prog() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+    let link = prog.attach().expect("failed to attach program");
+    let id = Program::get_id_by_fd(prog.as_fd()).unwrap();
+
+    let () = obj
+        .unload_prog("handle__sched_wakeup")
+        .expect("failed to unload program");
+    assert!(obj.prog("handle__sched_wakeup").is_none());
+    assert!(obj.prog("handle__sched_switch").is_some());
+
+    // The link still keeps the program alive.
+    let fd = Program::get_fd_by_id(id).expect("program unexpectedly gone");
+    drop(fd);
+    drop(link);
+
+    let err = obj.unload_prog("handle__sched_wakeup").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+/// Check that we can take ownership of a program's file descriptor,
+/// independent of the object it belongs to.
+#[test]
+fn test_sudo_object_take_prog_fd() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let id = Program::get_id_by_fd(obj.prog("handle__sched_wakeup").unwrap().as_fd()).unwrap();
+
+    let fd = obj
+        .take_prog_fd("handle__sched_wakeup")
+        .expect("failed to take program fd");
+    assert!(obj.prog("handle__sched_wakeup").is_none());
+    assert_eq!(Program::get_id_by_fd(fd.as_fd()).unwrap(), id);
+
+    // The program outlives its object.
+    drop(obj);
+    assert_eq!(Program::get_id_by_fd(fd.as_fd()).unwrap(), id);
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let err = obj.take_prog_fd("no_such_prog").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
 #[test]
 fn test_sudo_object_program_pin() {
     bump_rlimit_mlock();
@@ -752,19 +1255,209 @@ fn test_sudo_object_program_pin() {
     // Unpinning a unpinned prog should be an error
     assert!(prog.unpin(path).is_err());
     assert!(!Path::new(path).exists());
+    assert!(!prog.is_pinned());
+    assert_eq!(prog.get_pin_path(), None);
 
     // Pin should be successful
     prog.pin(path).expect("failed to pin prog");
     assert!(Path::new(path).exists());
+    assert!(prog.is_pinned());
+    assert_eq!(prog.get_pin_path(), Some(Path::new(path)));
 
     // Backup cleanup method in case test errors
     defer! {
         let _ = fs::remove_file(path);
     }
 
+    // The pinned program can be opened again
+    let fd = Program::get_fd_by_pinned_path(path).expect("failed to open pinned prog");
+    assert_eq!(
+        Program::get_id_by_fd(fd.as_fd()).unwrap(),
+        Program::get_id_by_fd(prog.as_fd()).unwrap()
+    );
+
     // Unpin should be successful
     prog.unpin(path).expect("failed to unpin prog");
     assert!(!Path::new(path).exists());
+    assert!(Program::get_fd_by_pinned_path(path).is_err());
+    assert!(!prog.is_pinned());
+    assert_eq!(prog.get_pin_path(), None);
+}
+
+/// Check that we can pin a program to multiple paths and that all of them
+/// are tracked.
+#[test]
+fn test_sudo_object_program_pin_multiple() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let path1 = Path::new("/sys/fs/bpf/myprog_multi1");
+    let path2 = Path::new("/sys/fs/bpf/myprog_multi2");
+    assert!(prog.pinned_paths().is_empty());
+
+    prog.pin(path1).expect("failed to pin prog");
+    defer! {
+        let _ = fs::remove_file(path1);
+    }
+    prog.pin(path2).expect("failed to pin prog");
+    defer! {
+        let _ = fs::remove_file(path2);
+    }
+    assert!(path1.exists());
+    assert!(path2.exists());
+    assert_eq!(prog.pinned_paths(), [path1, path2]);
+    assert_eq!(prog.get_pin_path(), Some(path2));
+
+    prog.unpin(path2).expect("failed to unpin prog");
+    assert!(!path2.exists());
+    assert_eq!(prog.pinned_paths(), [path1]);
+    assert_eq!(prog.get_pin_path(), Some(path1));
+    assert!(prog.is_pinned());
+
+    prog.unpin(path1).expect("failed to unpin prog");
+    assert!(prog.pinned_paths().is_empty());
+    assert!(!prog.is_pinned());
+}
+
+/// Check that detaching a program from a cgroup validates the attach type and
+/// reports when nothing is attached.
+#[test]
+fn test_sudo_object_program_detach_cgroup() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog("xdp_filter").expect("Failed to find program");
+    let cgroup = fs::File::open("/sys/fs/cgroup").expect("Failed to open cgroup");
+
+    let err = prog
+        .detach_cgroup(cgroup.as_fd(), ProgramAttachType::CgroupInetIngress)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let _err = prog
+        .detach_cgroup(cgroup.as_fd(), ProgramAttachType::Xdp)
+        .unwrap_err();
+}
+
+/// Check that only socket lookup programs can be attached via
+/// `Program::attach_sk_lookup`.
+#[test]
+fn test_sudo_object_program_attach_sk_lookup_invalid() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let netns = fs::File::open("/proc/self/ns/net").expect("Failed to open netns");
+
+    let err = prog.attach_sk_lookup(netns.as_fd()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+/// Check that only flow dissector programs can be attached via
+/// `Program::attach_flow_dissector`.
+#[test]
+fn test_sudo_object_program_attach_flow_dissector_invalid() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let netns = fs::File::open("/proc/self/ns/net").expect("Failed to open netns");
+
+    let err = prog.attach_flow_dissector(netns.as_fd()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+/// Check that we can query the programs attached to a cgroup.
+#[test]
+fn test_sudo_program_query_cgroup() {
+    bump_rlimit_mlock();
+
+    let cgroup = fs::File::open("/sys/fs/cgroup").expect("Failed to open cgroup");
+    let ids = Program::query_cgroup(cgroup.as_fd(), ProgramAttachType::CgroupInetIngress)
+        .expect("Failed to query cgroup");
+    assert!(!ids.contains(&0));
+
+    let _err = Program::query_cgroup(cgroup.as_fd(), ProgramAttachType::Xdp).unwrap_err();
+}
+
+/// Check that we can pin a program with custom permissions.
+#[test]
+fn test_sudo_object_program_pin_with_mode() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+
+    let path = "/sys/fs/bpf/myprog_mode";
+    prog.pin_with_mode(path, 0o644).expect("failed to pin prog");
+    defer! {
+        let _ = fs::remove_file(path);
+    }
+
+    let mode = fs::metadata(path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o644);
+}
+
+/// Check that `Program::get_fd_by_pinned_path` refuses to open objects other
+/// than programs.
+#[test]
+fn test_sudo_program_get_fd_by_pinned_path_map() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let map = obj.map_mut("start").expect("failed to find map 'start'");
+    let path = "/sys/fs/bpf/mymap_not_a_prog";
+
+    map.pin(path).expect("failed to pin map");
+    defer! {
+        let _ = fs::remove_file(path);
+    }
+
+    let err = Program::get_fd_by_pinned_path(path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+/// Check that `Program::try_unpin` tolerates absent pins and refuses to
+/// remove pins of other programs.
+#[test]
+fn test_sudo_object_program_try_unpin() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let path = "/sys/fs/bpf/myprog_try_unpin";
+
+    let prog = obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+    // Unpinning an unpinned prog is fine.
+    let () = prog.try_unpin(path).unwrap();
+
+    prog.pin(path).expect("failed to pin prog");
+    defer! {
+        let _ = fs::remove_file(path);
+    }
+
+    // Another program must not remove the pin.
+    let other = obj
+        .prog_mut("handle__sched_switch")
+        .expect("failed to find program");
+    let err = other.try_unpin(path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(Path::new(path).exists());
+
+    let prog = obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+    let () = prog.try_unpin(path).unwrap();
+    assert!(!Path::new(path).exists());
+    assert!(!prog.is_pinned());
+    let () = prog.try_unpin(path).unwrap();
 }
 
 #[test]
@@ -797,6 +1490,39 @@ fn test_sudo_object_link_pin() {
     assert!(!Path::new(path).exists());
 }
 
+/// Check that we can reopen a pinned link and introspect it.
+#[test]
+fn test_sudo_object_link_open_pinned() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("runqslower.bpf.o");
+    let prog = obj
+        .prog_mut("handle__sched_wakeup")
+        .expect("failed to find program");
+    let prog_id = Program::get_id_by_fd(prog.as_fd()).unwrap();
+    let mut link = prog.attach().expect("failed to attach prog");
+
+    let path = "/sys/fs/bpf/mylink_open_pinned";
+    link.pin(path).expect("failed to pin link");
+    defer! {
+        let _ = fs::remove_file(path);
+    }
+
+    let info = link.info().expect("failed to get link info");
+    assert_eq!(info.prog_id, prog_id);
+    assert!(matches!(info.info, LinkTypeInfo::Tracing(_)));
+
+    // The pin keeps the link attached after dropping our object.
+    drop(link);
+    let mut link = Link::open(path).expect("failed to open pinned link");
+    let reopened_info = link.info().expect("failed to get link info");
+    assert_eq!(reopened_info.id, info.id);
+    assert_eq!(reopened_info.prog_id, prog_id);
+
+    link.unpin().expect("failed to unpin link");
+    assert!(!Path::new(path).exists());
+}
+
 #[test]
 fn test_sudo_object_reuse_pined_map() {
     bump_rlimit_mlock();
@@ -1224,6 +1950,134 @@ fn test_sudo_object_task_iter() {
     assert!(items.iter().any(|&item| item.pid == 1));
 }
 
+/// Check that we can restrict a task iterator to a single process.
+#[test]
+fn test_sudo_object_task_iter_with_opts() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("taskiter.bpf.o");
+    let prog = obj.prog_mut("dump_pid").expect("Failed to find program");
+    let pid = unsafe { libc::getpid() };
+    let link = prog
+        .attach_iter_with_opts(IterOpts::Pid(pid as _))
+        .expect("Failed to attach prog");
+    let mut iter = Iter::new(&link).expect("Failed to create iterator");
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct IndexPidPair {
+        i: u32,
+        pid: i32,
+    }
+
+    unsafe impl Plain for IndexPidPair {}
+
+    let mut buf = Vec::new();
+    let bytes_read = iter
+        .read_to_end(&mut buf)
+        .expect("Failed to read from iterator");
+
+    assert!(bytes_read > 0);
+    let items: &[IndexPidPair] =
+        plain::slice_from_bytes(buf.as_slice()).expect("Input slice cannot satisfy length");
+    assert!(items.iter().all(|item| item.pid == pid));
+}
+
+/// Check that we can attach an extension program replacing another BPF
+/// program.
+#[test]
+fn test_sudo_attach_freplace() {
+    bump_rlimit_mlock();
+
+    let target_obj = get_test_object("xdp.bpf.o");
+    let target = target_obj
+        .prog("xdp_filter")
+        .expect("Failed to find program");
+
+    let mut open_obj = open_test_object("xdp.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("xdp_filter")
+        .expect("Failed to find program");
+    open_prog.set_prog_type(ProgramType::Ext);
+    let () = open_prog
+        .set_attach_target_fd(Some(target.as_fd()), Some("xdp_filter".to_string()))
+        .expect("Failed to set attach target");
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    assert!(matches!(prog.prog_type(), ProgramType::Ext));
+    let _link = prog
+        .attach_freplace(target.as_fd(), "xdp_filter")
+        .expect("Failed to attach extension program");
+}
+
+/// Check that we can attach an fentry program to a function of another
+/// BPF program.
+#[test]
+fn test_sudo_attach_trace_to_program() {
+    bump_rlimit_mlock();
+
+    let target_obj = get_test_object("xdp.bpf.o");
+    let target = target_obj
+        .prog("xdp_filter")
+        .expect("Failed to find program");
+
+    let mut open_obj = open_test_object("tracepoint.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+    open_prog.set_prog_type(ProgramType::Tracing);
+    open_prog.set_attach_type(ProgramAttachType::TraceFentry);
+    let err = open_prog
+        .set_attach_target_program(target, "libbpf_rs_no_such_function")
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    let () = open_prog
+        .set_attach_target_program(target, "xdp_filter")
+        .expect("Failed to set attach target");
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+    let _link = prog.attach_trace().expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let data = [0u8; 64];
+        let input = ProgramInput::new().data_in(&data);
+        let _retval = target.test_runner(input).unwrap().run().unwrap();
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
+/// Check that we can set a kernel function as attach target by name.
+#[test]
+fn test_sudo_set_attach_target_kernel() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("xdp.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("xdp_filter")
+        .expect("Failed to find program");
+    open_prog.set_prog_type(ProgramType::Tracing);
+    open_prog.set_attach_type(ProgramAttachType::TraceFentry);
+    let () = open_prog
+        .set_attach_target_kernel("bpf_fentry_test1")
+        .expect("Failed to set attach target");
+
+    let _err = open_prog
+        .set_attach_target_kernel("libbpf_rs_no_such_function")
+        .unwrap_err();
+
+    let err = open_prog
+        .set_attach_target_module("libbpf_rs_no_such_module", "bpf_fentry_test1")
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
 #[test]
 fn test_sudo_object_map_iter() {
     bump_rlimit_mlock();
@@ -1409,6 +2263,32 @@ fn test_sudo_object_usdt() {
     assert_eq!(result, 1);
 }
 
+/// Check that we can attach to a USDT probe in all processes by passing a
+/// PID of -1.
+#[test]
+fn test_sudo_object_usdt_all_processes() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("usdt.bpf.o");
+    let prog = obj
+        .prog_mut("handle__usdt")
+        .expect("Failed to find program");
+
+    let path = current_exe().expect("Failed to find executable name");
+    let _link = prog
+        .attach_usdt(-1, &path, "test_provider", "test_function")
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        // Define a USDT probe point and exercise it as we are attaching to self.
+        probe!(test_provider, test_function, 1);
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
 #[test]
 fn test_sudo_object_usdt_cookie() {
     bump_rlimit_mlock();
@@ -1463,7 +2343,58 @@ fn test_sudo_program_probes() {
         .is_supported()
         .expect("Failed to query if SocketFilter program is supported");
     assert!(supported);
-    let supported_res = ProgramType::Unknown.is_supported();
+    let supported_res = ProgramType::Unknown.is_supported();
+    assert!(supported_res.is_err());
+}
+
+/// Check that we can probe for support of program attach types.
+#[test]
+fn test_sudo_program_attach_type_probes() {
+    bump_rlimit_mlock();
+
+    for attach_type in [
+        ProgramAttachType::Xdp,
+        ProgramAttachType::CgroupInetIngress,
+        ProgramAttachType::CgroupInet4Connect,
+        ProgramAttachType::TraceFentry,
+        ProgramAttachType::TraceRawTp,
+        ProgramAttachType::PerfEvent,
+        ProgramAttachType::TraceKprobeMulti,
+        ProgramAttachType::TraceUprobeMulti,
+        ProgramAttachType::TcxIngress,
+        ProgramAttachType::TcxEgress,
+    ] {
+        let supported = attach_type
+            .is_supported()
+            .unwrap_or_else(|err| panic!("failed to probe {attach_type:?}: {err}"));
+        assert!(supported, "{attach_type:?}");
+    }
+
+    let err = ProgramAttachType::XdpDevmap.is_supported().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    let supported_res = ProgramAttachType::Unknown.is_supported();
+    assert!(supported_res.is_err());
+}
+
+/// Check that we can retrieve the log of a program type probe.
+#[test]
+fn test_sudo_program_probes_with_log() {
+    bump_rlimit_mlock();
+
+    let (supported, log) = ProgramType::SocketFilter
+        .is_supported_with_log()
+        .expect("Failed to query if SocketFilter program is supported");
+    assert!(supported);
+    assert!(log.contains("processed 2 insns"), "{log}");
+
+    let (supported, log) = ProgramType::Tracing
+        .is_supported_with_log()
+        .expect("Failed to query if Tracing program is supported");
+    assert!(supported);
+    assert!(log.contains("attach_btf_id 1 is not a function"), "{log}");
+
+    let supported_res = ProgramType::Unknown.is_supported_with_log();
     assert!(supported_res.is_err());
 }
 
@@ -1484,6 +2415,40 @@ fn test_sudo_program_helper_probes() {
     assert!(supported_res.is_err());
 }
 
+/// Check that we can enumerate the helpers supported by a program type.
+#[test]
+fn test_sudo_program_supported_helpers() {
+    bump_rlimit_mlock();
+
+    let helpers = ProgramType::SocketFilter
+        .supported_helpers()
+        .expect("Failed to query supported helpers");
+    assert!(helpers.contains(&libbpf_sys::BPF_FUNC_map_lookup_elem));
+    assert!(!helpers.contains(&libbpf_sys::BPF_FUNC_redirect));
+}
+
+/// Check that we can disassemble a program's instructions.
+#[test]
+fn test_object_open_program_disassemble() {
+    let open_obj = open_test_object("xdp.bpf.o");
+    let prog = open_obj.prog("xdp_filter").expect("Failed to find program");
+    let text = prog.disassemble();
+    assert_eq!(text.lines().count(), prog.insn_cnt());
+    assert!(text.ends_with("(95) exit\n"), "{text}");
+}
+
+/// Check that we can decode a program's instructions.
+#[test]
+fn test_object_open_program_decoded_insns() {
+    let open_obj = open_test_object("xdp.bpf.o");
+    let prog = open_obj.prog("xdp_filter").expect("Failed to find program");
+    let insns = prog.decoded_insns().collect::<Vec<_>>();
+    let last = insns.last().unwrap();
+    assert_eq!(last.mnemonic, "exit");
+    assert_eq!(last.class, InsnClass::Jmp);
+    assert_eq!(last.idx + last.slots(), prog.insn_cnt());
+}
+
 #[test]
 fn test_sudo_object_open_program_insns() {
     bump_rlimit_mlock();
@@ -1531,6 +2496,191 @@ fn test_sudo_object_tracepoint() {
     assert_eq!(result, 1);
 }
 
+/// Check that program run time statistics are collected once enabled.
+#[test]
+fn test_sudo_object_program_run_stats() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("tracepoint.bpf.o");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+
+    let _stats = enable_stats().expect("Failed to enable statistics");
+    let _link = prog
+        .attach_tracepoint("syscalls", "sys_enter_getpid")
+        .expect("Failed to attach prog");
+
+    let _pid = unsafe { libc::getpid() };
+
+    assert!(prog.run_cnt().unwrap() >= 1);
+    assert!(prog.run_time_ns().unwrap() > 0);
+}
+
+/// Check that we can retrieve a program's recursion misses.
+#[test]
+fn test_sudo_object_program_recursion_misses() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("tracepoint.bpf.o");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+
+    let _link = prog
+        .attach_tracepoint("syscalls", "sys_enter_getpid")
+        .expect("Failed to attach prog");
+    let _pid = unsafe { libc::getpid() };
+
+    // The program does not trigger itself.
+    assert_eq!(prog.recursion_misses().unwrap(), 0);
+}
+
+/// Retrieve the perf event id of the given kernel tracepoint.
+fn tracepoint_id(tp_category: &str, tp_name: &str) -> u64 {
+    let id = ["/sys/kernel/tracing", "/sys/kernel/debug/tracing"]
+        .iter()
+        .find_map(|tracefs| {
+            fs::read_to_string(format!("{tracefs}/events/{tp_category}/{tp_name}/id")).ok()
+        })
+        .expect("failed to read tracepoint id");
+    id.trim().parse().expect("failed to parse tracepoint id")
+}
+
+/// Open a perf event for the given kernel tracepoint, monitoring the calling
+/// process.
+fn open_tracepoint_perf_event(tp_category: &str, tp_name: &str) -> OwnedFd {
+    let mut attr = libbpf_sys::perf_event_attr {
+        type_: libbpf_sys::PERF_TYPE_TRACEPOINT,
+        size: size_of::<libbpf_sys::perf_event_attr>() as _,
+        config: tracepoint_id(tp_category, tp_name),
+        ..Default::default()
+    };
+    attr.__bindgen_anon_1.sample_period = 1;
+    attr.__bindgen_anon_2.wakeup_events = 1;
+
+    let fd = unsafe {
+        libc::syscall(
+            libc::SYS_perf_event_open,
+            &attr as *const _,
+            0,
+            -1,
+            -1,
+            libbpf_sys::PERF_FLAG_FD_CLOEXEC,
+        )
+    };
+    assert!(
+        fd >= 0,
+        "perf_event_open failed: {}",
+        io::Error::last_os_error()
+    );
+    unsafe { OwnedFd::from_raw_fd(fd as _) }
+}
+
+/// Check that we can attach a BPF program to a perf event and access the
+/// cookie provided during attach.
+#[test]
+fn test_sudo_object_perf_event_with_cookie() {
+    bump_rlimit_mlock();
+
+    let cookie_val = 13u16;
+    let mut obj = get_test_object("tracepoint.bpf.o");
+    let prog = obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("Failed to find program");
+
+    let pfd = open_tracepoint_perf_event("syscalls", "sys_enter_getpid");
+    let opts = PerfEventOpts {
+        cookie: cookie_val.into(),
+        ..Default::default()
+    };
+    let link = prog
+        .attach_perf_event_with_opts(pfd, opts)
+        .expect("Failed to attach prog");
+    assert_eq!(link.cookie(), cookie_val.into());
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let _pid = unsafe { libc::getpid() };
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, cookie_val.into());
+}
+
+/// Check that we can have a perf event opened for us and attach a BPF
+/// program to it in one go.
+#[test]
+fn test_sudo_object_perf_event_config() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("tracepoint.bpf.o");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+
+    // Tracepoint programs can't be attached to software events.
+    let _err = prog
+        .attach_perf_event_config(PerfEventConfig::default())
+        .unwrap_err();
+
+    let config = PerfEventConfig {
+        type_: libbpf_sys::PERF_TYPE_TRACEPOINT,
+        config: tracepoint_id("syscalls", "sys_enter_getpid"),
+        sampling: PerfEventSampling::Period(1),
+        ..Default::default()
+    };
+    let _link = prog
+        .attach_perf_event_config(config)
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let _pid = unsafe { libc::getpid() };
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
+/// Check that we can attach a BPF program to a perf event on each online
+/// CPU.
+#[test]
+fn test_sudo_object_perf_event_per_cpu() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("tracepoint.bpf.o");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+
+    let config = PerfEventConfig {
+        type_: libbpf_sys::PERF_TYPE_TRACEPOINT,
+        config: tracepoint_id("syscalls", "sys_enter_getpid"),
+        sampling: PerfEventSampling::Period(1),
+        pid: -1,
+        ..Default::default()
+    };
+    let links = prog
+        .attach_perf_event_per_cpu(config)
+        .expect("Failed to determine online CPUs")
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Failed to attach prog");
+    assert!(!links.is_empty());
+    assert!(links.len() <= num_possible_cpus().unwrap());
+    // Cookies are CPU IDs, in ascending order.
+    assert!(links.windows(2).all(|w| w[0].cookie() < w[1].cookie()));
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let _pid = unsafe { libc::getpid() };
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
 /// Check that we can attach a BPF program to a kernel tracepoint, providing
 /// additional options.
 #[test]
@@ -1594,40 +2744,268 @@ fn test_sudo_object_uprobe_with_opts() {
     };
     let result = with_ringbuffer(map, action);
 
-    assert_eq!(result, 1);
+    assert_eq!(result, 1);
+}
+
+/// Check that we can attach a uprobe to the site of a USDT probe.
+#[test]
+fn test_sudo_object_uprobe_from_usdt() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("uprobe.bpf.o");
+    let prog = obj
+        .prog_mut("handle__uprobe")
+        .expect("Failed to find program");
+
+    let pid = unsafe { libc::getpid() };
+    let path = current_exe().expect("Failed to find executable name");
+    let err = UprobeOpts::from_usdt(&path, "test_provider", "does_not_exist").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let (opts, func_offset) =
+        UprobeOpts::from_usdt(&path, "test_provider", "test_uprobe_usdt").unwrap();
+    assert_eq!(opts.ref_ctr_offset, 0);
+    let _link = prog
+        .attach_uprobe_with_opts(pid, &path, func_offset, opts)
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        probe!(test_provider, test_uprobe_usdt, 1);
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
+/// The semaphore of the `test_provider:test_uprobe_usdt_sema` USDT probe,
+/// which the kernel increments while a uprobe with `ref_ctr_offset` is
+/// attached to it.
+#[link_section = ".probes"]
+static USDT_SEMAPHORE: AtomicU16 = AtomicU16::new(0);
+
+/// Fire the `test_provider:test_uprobe_usdt_sema` USDT probe.
+///
+/// The `probe` crate does not support semaphores, so the probe is defined
+/// by hand, following the layout of `sys/sdt.h`.
+#[inline(never)]
+fn fire_usdt_with_semaphore() {
+    unsafe {
+        asm!(
+            "990: nop",
+            ".pushsection .note.stapsdt,\"?\",\"note\"",
+            ".balign 4",
+            ".4byte 992f-991f, 994f-993f, 3",
+            "991: .asciz \"stapsdt\"",
+            "992: .balign 4",
+            "993: .8byte 990b",
+            ".8byte _.stapsdt.base",
+            ".8byte {sema}",
+            ".asciz \"test_provider\"",
+            ".asciz \"test_uprobe_usdt_sema\"",
+            ".asciz \"\"",
+            "994: .balign 4",
+            ".popsection",
+            ".ifndef _.stapsdt.base",
+            ".pushsection .stapsdt.base,\"aG\",\"progbits\",.stapsdt.base,comdat",
+            ".weak _.stapsdt.base",
+            ".hidden _.stapsdt.base",
+            "_.stapsdt.base: .space 1",
+            ".size _.stapsdt.base, 1",
+            ".popsection",
+            ".endif",
+            sema = sym USDT_SEMAPHORE,
+            options(nostack, preserves_flags),
+        )
+    }
+}
+
+/// Check that we can attach a uprobe to the site of a USDT probe guarded by
+/// a semaphore, and that the kernel enables the probe via the semaphore.
+#[test]
+fn test_sudo_object_uprobe_from_usdt_semaphore() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("uprobe.bpf.o");
+    let prog = obj
+        .prog_mut("handle__uprobe")
+        .expect("Failed to find program");
+
+    let pid = unsafe { libc::getpid() };
+    let path = current_exe().expect("Failed to find executable name");
+    let (opts, func_offset) =
+        UprobeOpts::from_usdt(&path, "test_provider", "test_uprobe_usdt_sema").unwrap();
+    assert_ne!(opts.ref_ctr_offset, 0);
+
+    assert_eq!(USDT_SEMAPHORE.load(Ordering::Relaxed), 0);
+    let link = prog
+        .attach_uprobe_with_opts(pid, &path, func_offset, opts)
+        .expect("Failed to attach prog");
+    assert_eq!(USDT_SEMAPHORE.load(Ordering::Relaxed), 1);
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let result = with_ringbuffer(map, fire_usdt_with_semaphore);
+    assert_eq!(result, 1);
+
+    drop(link);
+    assert_eq!(USDT_SEMAPHORE.load(Ordering::Relaxed), 0);
+}
+
+/// Check that we can attach a BPF program to a uprobe by symbol name.
+#[test]
+fn test_sudo_object_uprobe_symbol() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("uprobe.bpf.o");
+    let prog = obj
+        .prog_mut("handle__uprobe")
+        .expect("Failed to find program");
+
+    let pid = unsafe { libc::getpid() };
+    let path = current_exe().expect("Failed to find executable name");
+    let err = prog
+        .attach_uprobe_symbol(pid, &path, "uprobe_target_does_not_exist", false)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let _link = prog
+        .attach_uprobe_symbol(pid, &path, "uprobe_target", false)
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let _ = uprobe_target();
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
+/// Check that we can attach a BPF program to a uprobe and access the cookie
+/// provided during attach.
+#[test]
+fn test_sudo_object_uprobe_with_cookie() {
+    bump_rlimit_mlock();
+
+    let cookie_val = 5u16;
+    let mut obj = get_test_object("uprobe.bpf.o");
+    let prog = obj
+        .prog_mut("handle__uprobe_with_cookie")
+        .expect("Failed to find program");
+
+    let pid = unsafe { libc::getpid() };
+    let path = current_exe().expect("Failed to find executable name");
+    let func_offset = 0;
+    let opts = UprobeOpts {
+        func_name: "uprobe_target".to_string(),
+        cookie: cookie_val.into(),
+        ..Default::default()
+    };
+    let link = prog
+        .attach_uprobe_with_opts(pid, path, func_offset, opts)
+        .expect("Failed to attach prog");
+    assert_eq!(link.cookie(), cookie_val.into());
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let _ = uprobe_target();
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, cookie_val.into());
+}
+
+/// Check that we can attach a program to multiple uprobes at once and
+/// access the cookie provided for each attach point.
+#[test]
+fn test_sudo_object_uprobe_multi() {
+    bump_rlimit_mlock();
+
+    let cookie_val = 7u16;
+    let mut open_obj = open_test_object("uprobe.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__uprobe_with_cookie")
+        .expect("Failed to find program");
+    open_prog.set_attach_type(ProgramAttachType::TraceUprobeMulti);
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__uprobe_with_cookie")
+        .expect("Failed to find program");
+
+    let pid = unsafe { libc::getpid() };
+    let path = current_exe().expect("Failed to find executable name");
+    let opts = UprobeMultiOpts {
+        symbols: vec!["uprobe_target".to_string()],
+        cookies: vec![cookie_val.into()],
+        ..Default::default()
+    };
+    let _link = prog
+        .attach_uprobe_multi(pid, path, opts)
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let _ = uprobe_target();
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, cookie_val.into());
 }
 
-/// Check that we can attach a BPF program to a uprobe and access the cookie
-/// provided during attach.
+/// Check that `Program::attach_uprobes` handles empty input and reports
+/// failure to attach to any of the provided offsets.
 #[test]
-fn test_sudo_object_uprobe_with_cookie() {
+fn test_sudo_object_uprobes() {
     bump_rlimit_mlock();
 
-    let cookie_val = 5u16;
     let mut obj = get_test_object("uprobe.bpf.o");
     let prog = obj
-        .prog_mut("handle__uprobe_with_cookie")
+        .prog_mut("handle__uprobe")
         .expect("Failed to find program");
 
     let pid = unsafe { libc::getpid() };
     let path = current_exe().expect("Failed to find executable name");
-    let func_offset = 0;
-    let opts = UprobeOpts {
-        func_name: "uprobe_target".to_string(),
-        cookie: cookie_val.into(),
-        ..Default::default()
-    };
-    let _link = prog
-        .attach_uprobe_with_opts(pid, path, func_offset, opts)
+    let links = prog
+        .attach_uprobes(pid, &path, &[], false)
         .expect("Failed to attach prog");
+    assert!(links.is_empty());
 
-    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
-    let action = || {
-        let _ = uprobe_target();
-    };
-    let result = with_ringbuffer(map, action);
+    // An offset beyond the end of the binary can't be attached to.
+    let _err = prog
+        .attach_uprobes(pid, &path, &[1 << 40], false)
+        .unwrap_err();
+}
 
-    assert_eq!(result, cookie_val.into());
+/// Check that we can read back the attach type, ifindex, and flags set on
+/// an `OpenProgram`.
+#[test]
+fn test_object_open_program_getters() {
+    let mut open_obj = open_test_object("xdp.bpf.o");
+    let prog = open_obj
+        .prog_mut("xdp_filter")
+        .expect("failed to find program");
+    assert_eq!(prog.ifindex(), 0);
+
+    let () = prog.set_attach_type(ProgramAttachType::XdpDevmap);
+    let () = prog.set_ifindex(1);
+    let () = prog.set_flags(libbpf_sys::BPF_F_XDP_HAS_FRAGS).unwrap();
+    assert_eq!(
+        prog.attach_type() as u32,
+        ProgramAttachType::XdpDevmap as u32
+    );
+    assert_eq!(prog.ifindex(), 1);
+    assert_eq!(prog.flags(), libbpf_sys::BPF_F_XDP_HAS_FRAGS);
+}
+
+/// Check that we can access the raw `bpf_program` of an `OpenProgram`.
+#[test]
+fn test_object_open_program_as_libbpf_object() {
+    let open_obj = open_test_object("xdp.bpf.o");
+    let prog = open_obj.prog("xdp_filter").expect("failed to find program");
+    let ptr = prog.as_libbpf_object();
+    let name = unsafe { CStr::from_ptr(libbpf_sys::bpf_program__name(ptr.as_ptr())) };
+    assert_eq!(name.to_str().unwrap(), "xdp_filter");
 }
 
 /// Check that we can link multiple object files.
@@ -1844,6 +3222,405 @@ fn test_sudo_attach_ksyscall() {
     assert_eq!(result, 1);
 }
 
+/// Check that we can attach a BPF program to a kprobe and access the cookie
+/// provided during attach.
+#[test]
+fn test_sudo_object_kprobe_with_cookie() {
+    bump_rlimit_mlock();
+
+    let cookie_val = 9u16;
+    let mut open_obj = open_test_object("tracepoint.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("Failed to find program");
+    open_prog.set_prog_type(ProgramType::Kprobe);
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("Failed to find program");
+
+    let opts = KprobeOpts {
+        cookie: cookie_val.into(),
+        ..Default::default()
+    };
+    let link = prog
+        .attach_kprobe_with_opts(false, "do_nanosleep", opts)
+        .expect("Failed to attach prog");
+    assert_eq!(link.cookie(), cookie_val.into());
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let () = thread::sleep(Duration::from_millis(1));
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, cookie_val.into());
+}
+
+/// Check that we can attach a BPF program to a kretprobe.
+#[test]
+fn test_sudo_object_kretprobe() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("tracepoint.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+    open_prog.set_prog_type(ProgramType::Kprobe);
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+
+    let _link = prog
+        .attach_kretprobe("do_nanosleep")
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let () = thread::sleep(Duration::from_millis(1));
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
+/// Check that we can attach a BPF program to a kprobe using an explicit
+/// attach mode.
+#[test]
+fn test_sudo_object_kprobe_attach_mode() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("tracepoint.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+    open_prog.set_prog_type(ProgramType::Kprobe);
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+
+    for attach_mode in [ProbeAttachMode::Perf, ProbeAttachMode::Link] {
+        let opts = KprobeOpts::default().attach_mode(attach_mode);
+        let _link = prog
+            .attach_kprobe_with_opts(false, "do_nanosleep", opts)
+            .unwrap_or_else(|err| panic!("failed to attach prog with {attach_mode:?}: {err:?}"));
+    }
+}
+
+/// Check that we are able to attach using ksyscall and access the cookie
+/// provided during attach.
+#[test]
+fn test_sudo_attach_ksyscall_with_cookie() {
+    bump_rlimit_mlock();
+
+    let cookie_val = 11u16;
+    let mut open_obj = open_test_object("tracepoint.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("Failed to find program");
+    open_prog.set_prog_type(ProgramType::Kprobe);
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("Failed to find program");
+
+    let opts = KsyscallOpts {
+        cookie: cookie_val.into(),
+        ..Default::default()
+    };
+    let _link = prog
+        .attach_ksyscall_with_opts(false, "getpid", opts)
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        let _pid = unsafe { libc::getpid() };
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, cookie_val.into());
+}
+
+/// Check that we can attach a program to multiple kprobes at once.
+#[test]
+fn test_sudo_attach_kprobe_multi() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("ksyscall.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__ksyscall")
+        .expect("Failed to find program");
+    open_prog.set_attach_type(ProgramAttachType::TraceKprobeMulti);
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__ksyscall")
+        .expect("Failed to find program");
+
+    let opts = KprobeMultiOpts {
+        pattern: Some("*sys_kill".to_string()),
+        ..Default::default()
+    };
+    let _link = prog
+        .attach_kprobe_multi_with_opts(opts)
+        .expect("Failed to attach prog");
+
+    let map = obj.map("ringbuf").expect("Failed to get ringbuf map");
+    let action = || {
+        // Send `SIGCHLD`, which is ignored by default, to our process.
+        let ret = unsafe { libc::kill(libc::getpid(), libc::SIGCHLD) };
+        if ret < 0 {
+            panic!("kill failed: {}", io::Error::last_os_error());
+        }
+    };
+    let result = with_ringbuffer(map, action);
+
+    assert_eq!(result, 1);
+}
+
+/// Check that we reject a cookie list not matching the attach points.
+#[test]
+fn test_sudo_attach_kprobe_multi_cookie_mismatch() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("ksyscall.bpf.o");
+    let open_prog = open_obj
+        .prog_mut("handle__ksyscall")
+        .expect("Failed to find program");
+    open_prog.set_attach_type(ProgramAttachType::TraceKprobeMulti);
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj
+        .prog_mut("handle__ksyscall")
+        .expect("Failed to find program");
+
+    let opts = KprobeMultiOpts {
+        symbols: vec!["do_nanosleep".to_string()],
+        cookies: vec![1, 2],
+        ..Default::default()
+    };
+    let err = prog.attach_kprobe_multi_with_opts(opts).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+/// Check that a program with autoattach disabled does not get attached when
+/// attaching a skeleton.
+#[test]
+fn test_sudo_skeleton_autoattach() {
+    bump_rlimit_mlock();
+
+    let data = fs::read(get_test_object_path("tracepoint.bpf.o")).unwrap();
+    let mut builder = ObjectSkeletonConfigBuilder::new(&data);
+    builder
+        .name("tracepoint")
+        .prog("handle__tracepoint")
+        .prog("handle__tracepoint_with_cookie");
+    let mut config = builder.build().expect("Failed to build skeleton config");
+
+    let ret = unsafe { libbpf_sys::bpf_object__open_skeleton(config.get(), ptr::null()) };
+    assert_eq!(ret, 0);
+    let mut open_obj = unsafe { OpenObject::from_ptr(config.object_ptr()) }.unwrap();
+    let prog = open_obj
+        .prog_mut("handle__tracepoint_with_cookie")
+        .expect("Failed to find program");
+    assert!(prog.autoattach());
+    prog.set_autoattach(false);
+    assert!(!prog.autoattach());
+
+    let ret = unsafe { libbpf_sys::bpf_object__load_skeleton(config.get()) };
+    assert_eq!(ret, 0);
+    let obj = unsafe { Object::from_ptr(open_obj.take_ptr()) }.unwrap();
+    let prog = obj
+        .prog("handle__tracepoint_with_cookie")
+        .expect("Failed to find program");
+    assert!(!prog.autoattach());
+
+    let skel: *mut _ = config.get();
+    let ret = unsafe { libbpf_sys::bpf_object__attach_skeleton(skel) };
+    assert_eq!(ret, 0);
+    defer! {
+        unsafe { libbpf_sys::bpf_object__detach_skeleton(skel) };
+    }
+
+    assert!(!config.prog_link_ptr(0).unwrap().is_null());
+    assert!(config.prog_link_ptr(1).unwrap().is_null());
+}
+
+/// Check that we can replace a program's instructions before load.
+#[test]
+fn test_sudo_object_program_set_insns() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("xdp.bpf.o");
+    let prog = open_obj
+        .prog_mut("xdp_filter")
+        .expect("Failed to find program");
+
+    // r0 = XDP_DROP; exit
+    let insns = [
+        libbpf_sys::bpf_insn {
+            code: (libbpf_sys::BPF_ALU64 | libbpf_sys::BPF_MOV | libbpf_sys::BPF_K) as _,
+            imm: libbpf_sys::XDP_DROP as _,
+            ..Default::default()
+        },
+        libbpf_sys::bpf_insn {
+            code: (libbpf_sys::BPF_JMP | libbpf_sys::BPF_EXIT) as _,
+            ..Default::default()
+        },
+    ];
+    let () = prog.set_insns(&insns).expect("Failed to set instructions");
+    assert_eq!(prog.insn_cnt(), insns.len());
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let data = [0u8; 64];
+    let input = ProgramInput {
+        data_in: Some(&data),
+        ..Default::default()
+    };
+    let output = prog.test_run(input).unwrap();
+    assert_eq!(output.return_value, libbpf_sys::XDP_DROP);
+}
+
+/// Check that we can retrieve a negative program return value.
+#[test]
+fn test_sudo_run_prog_return_value_signed() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("xdp.bpf.o");
+    let prog = open_obj
+        .prog_mut("xdp_filter")
+        .expect("Failed to find program");
+
+    // Socket filters may return arbitrary values.
+    let () = prog.set_prog_type(ProgramType::SocketFilter);
+
+    // r0 = -EPERM; exit
+    let insns = [
+        libbpf_sys::bpf_insn {
+            code: (libbpf_sys::BPF_ALU64 | libbpf_sys::BPF_MOV | libbpf_sys::BPF_K) as _,
+            imm: -libc::EPERM,
+            ..Default::default()
+        },
+        libbpf_sys::bpf_insn {
+            code: (libbpf_sys::BPF_JMP | libbpf_sys::BPF_EXIT) as _,
+            ..Default::default()
+        },
+    ];
+    let () = prog.set_insns(&insns).expect("Failed to set instructions");
+
+    let mut obj = open_obj.load().expect("Failed to load object");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let data = [0u8; 64];
+    let input = ProgramInput {
+        data_in: Some(&data),
+        ..Default::default()
+    };
+    let output = prog.test_run(input).unwrap();
+    assert_eq!(output.return_value_signed(), -libc::EPERM);
+}
+
+/// Check that we can capture output data without providing a buffer.
+#[test]
+fn test_sudo_run_prog_capture_data_out() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let data = [0x42u8; 64];
+    let input = ProgramInput::new().data_in(&data).capture_data_out(true);
+    let output = prog.test_run(input).unwrap();
+    assert_eq!(output.return_value, libbpf_sys::XDP_PASS);
+    assert!(output.data.is_none());
+    assert_eq!(output.captured_data.as_deref(), Some(data.as_slice()));
+}
+
+/// Check that we can run a program repeatedly.
+#[test]
+fn test_sudo_run_prog_repeat() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let data = [0u8; 64];
+    let input = ProgramInput::new().data_in(&data).repeat(100);
+    let output = prog.test_run(input).unwrap();
+    assert_eq!(output.return_value, libbpf_sys::XDP_PASS);
+    assert_ne!(output.duration, Duration::ZERO);
+}
+
+/// Check that we can run an XDP program with live frames in batches.
+#[test]
+fn test_sudo_run_prog_batch_size() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let data = [0u8; 64];
+
+    // A batch size is only accepted when running with live frames.
+    let input = ProgramInput {
+        data_in: Some(&data),
+        batch_size: 16,
+        ..Default::default()
+    };
+    let _err = prog.test_run(input).unwrap_err();
+
+    let input = ProgramInput {
+        data_in: Some(&data),
+        xdp_live_frames: true,
+        repeat: 32,
+        batch_size: 16,
+        ..Default::default()
+    };
+    let _output = prog.test_run(input).unwrap();
+}
+
+/// Check that invalid live frames mode inputs are rejected.
+#[test]
+fn test_sudo_run_prog_xdp_live_frames_invalid() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").expect("Failed to find program");
+    let data = [0u8; 64];
+    let mut data_out = [0u8; 64];
+
+    let input = ProgramInput {
+        xdp_live_frames: true,
+        ..Default::default()
+    };
+    let err = prog.test_run(input).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let input = ProgramInput {
+        data_in: Some(&data),
+        data_out: Some(&mut data_out),
+        flags: libbpf_sys::BPF_F_TEST_XDP_LIVE_FRAMES,
+        ..Default::default()
+    };
+    let err = prog.test_run(input).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut obj = get_test_object("tracepoint.bpf.o");
+    let prog = obj
+        .prog_mut("handle__tracepoint")
+        .expect("Failed to find program");
+    let input = ProgramInput {
+        data_in: Some(&data),
+        xdp_live_frames: true,
+        ..Default::default()
+    };
+    let err = prog.test_run(input).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
 /// Check that we can invoke a program directly.
 #[test]
 fn test_sudo_run_prog_success() {
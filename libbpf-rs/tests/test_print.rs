@@ -12,6 +12,7 @@ use libbpf_rs::PrintCallback;
 use libbpf_rs::PrintLevel;
 use serial_test::serial;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 
 #[test]
@@ -77,3 +78,27 @@ fn test_set_and_save_print() {
     let prev = set_print(None);
     assert_eq!(prev, Some((PrintLevel::Debug, callback2 as PrintCallback)));
 }
+
+/// Check that libbpf messages emitted from within the callback don't cause
+/// it to be invoked recursively.
+#[test]
+#[serial]
+fn test_set_print_reentrant() {
+    static DEPTH: AtomicUsize = AtomicUsize::new(0);
+    static MAX_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+    fn callback(_: PrintLevel, _: String) {
+        let depth = DEPTH.fetch_add(1, Ordering::Relaxed) + 1;
+        MAX_DEPTH.fetch_max(depth, Ordering::Relaxed);
+        // Trigger another warning from within the callback.
+        let _obj = ObjectBuilder::default().open_file("/dev/null");
+        DEPTH.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    set_print(Some((PrintLevel::Debug, callback)));
+    let obj = ObjectBuilder::default().open_file("/dev/null");
+    assert!(obj.is_err(), "Successfully loaded /dev/null?");
+    set_print(None);
+
+    assert_eq!(MAX_DEPTH.load(Ordering::Relaxed), 1);
+}
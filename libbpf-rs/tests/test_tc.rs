@@ -6,11 +6,19 @@ use serial_test::serial;
 mod test;
 use test::bump_rlimit_mlock;
 use test::get_test_object;
+use test::open_test_object;
 
 use libbpf_rs::ErrorKind;
+use libbpf_rs::Object;
+use libbpf_rs::Program;
+use libbpf_rs::ProgramAttachType;
+use libbpf_rs::ProgramInput;
 use libbpf_rs::Result;
+use libbpf_rs::SkBuffContext;
 use libbpf_rs::TcHook;
 use libbpf_rs::TcHookBuilder;
+use libbpf_rs::TcxAttachPoint;
+use libbpf_rs::TcxOpts;
 use libbpf_rs::TC_CUSTOM;
 use libbpf_rs::TC_EGRESS;
 use libbpf_rs::TC_H_CLSACT;
@@ -368,3 +376,138 @@ fn test_sudo_tc_double_create() {
 
     assert!(clear_clsact(fd).is_ok());
 }
+
+#[test]
+#[serial]
+fn test_sudo_tcx_attach() {
+    bump_rlimit_mlock();
+
+    let mut open_obj = open_test_object("tc-unit.bpf.o");
+    open_obj
+        .prog_mut("handle_tc")
+        .unwrap()
+        .set_attach_type(ProgramAttachType::TcxIngress);
+    let mut obj = open_obj.load().unwrap();
+    let prog = obj.prog_mut("handle_tc").unwrap();
+
+    let opts = TcxOpts {
+        attach_point: TcxAttachPoint::Ingress,
+        ..Default::default()
+    };
+    let _link = prog.attach_tcx(LO_IFINDEX, opts).unwrap();
+
+    // The program was not loaded for egress.
+    let opts = TcxOpts {
+        attach_point: TcxAttachPoint::Egress,
+        ..Default::default()
+    };
+    let err = prog.attach_tcx(LO_IFINDEX, opts).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+fn get_tcx_ingress_object() -> Object {
+    let mut open_obj = open_test_object("tc-unit.bpf.o");
+    open_obj
+        .prog_mut("handle_tc")
+        .unwrap()
+        .set_attach_type(ProgramAttachType::TcxIngress);
+    open_obj.load().unwrap()
+}
+
+/// Check that the program of a TCX link can only be replaced by one with
+/// the same attach type.
+#[test]
+#[serial]
+fn test_sudo_tcx_link_update_program() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_tcx_ingress_object();
+    let prog = obj.prog_mut("handle_tc").unwrap();
+    let mut link = prog.attach_tcx(LO_IFINDEX, TcxOpts::default()).unwrap();
+
+    let obj1 = get_tcx_ingress_object();
+    let prog1 = obj1.prog("handle_tc").unwrap();
+    let () = link.update_program(prog1).unwrap();
+
+    let mut open_obj = open_test_object("tc-unit.bpf.o");
+    open_obj
+        .prog_mut("handle_tc")
+        .unwrap()
+        .set_attach_type(ProgramAttachType::TcxEgress);
+    let obj2 = open_obj.load().unwrap();
+    let prog2 = obj2.prog("handle_tc").unwrap();
+    let err = link.update_program(prog2).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+/// Check that we can control and query the order of programs attached
+/// to a TCX hook.
+#[test]
+#[serial]
+fn test_sudo_tcx_attach_order() {
+    bump_rlimit_mlock();
+
+    let mut obj1 = get_tcx_ingress_object();
+    let prog1 = obj1.prog_mut("handle_tc").unwrap();
+    let prog1_id = Program::get_id_by_fd(prog1.as_fd()).unwrap();
+    let mut obj2 = get_tcx_ingress_object();
+    let prog2 = obj2.prog_mut("handle_tc").unwrap();
+    let prog2_id = Program::get_id_by_fd(prog2.as_fd()).unwrap();
+    let mut obj3 = get_tcx_ingress_object();
+    let prog3 = obj3.prog_mut("handle_tc").unwrap();
+    let prog3_id = Program::get_id_by_fd(prog3.as_fd()).unwrap();
+
+    let link1 = prog1.attach_tcx(LO_IFINDEX, TcxOpts::default()).unwrap();
+    let link1_id = link1.info().unwrap().id;
+    let opts = TcxOpts::default().before(&link1).unwrap();
+    let link2 = prog2.attach_tcx(LO_IFINDEX, opts).unwrap();
+    let link2_id = link2.info().unwrap().id;
+    let opts = TcxOpts::default().after(&link2).unwrap();
+    let link3 = prog3.attach_tcx(LO_IFINDEX, opts).unwrap();
+    let link3_id = link3.info().unwrap().id;
+
+    let chain = Program::query_tcx(LO_IFINDEX, TcxAttachPoint::Ingress).unwrap();
+    assert_eq!(chain.prog_ids, vec![prog2_id, prog3_id, prog1_id]);
+    assert_eq!(chain.link_ids, vec![link2_id, link3_id, link1_id]);
+    assert_ne!(chain.revision, 0);
+
+    drop(link2);
+    let chain = Program::query_tcx(LO_IFINDEX, TcxAttachPoint::Ingress).unwrap();
+    assert_eq!(chain.prog_ids, vec![prog3_id, prog1_id]);
+
+    let chain = Program::query_tcx(LO_IFINDEX, TcxAttachPoint::Egress).unwrap();
+    assert!(chain.prog_ids.is_empty());
+}
+
+/// Check that we can test run a TC program with a context.
+#[test]
+fn test_sudo_tc_test_run_context() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("tc-unit.bpf.o");
+    let prog = obj.prog_mut("handle_tc").unwrap();
+
+    let data = [0u8; 64];
+    let ctx = SkBuffContext::new()
+        .mark(42)
+        .ifindex(LO_IFINDEX as _)
+        .cb([1, 2, 3, 4, 5])
+        .to_bytes();
+    let input = ProgramInput::new().context_in(&ctx).data_in(&data);
+    let output = prog.test_run(input).unwrap();
+    // TC_ACT_UNSPEC
+    assert_eq!(output.return_value_signed(), -1);
+}
+
+/// Check that `Program::attach` reports a meaningful error for sections
+/// not supporting auto-attachment.
+#[test]
+fn test_sudo_tc_auto_attach_unsupported() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("tc-unit.bpf.o");
+    let prog = obj.prog_mut("handle_tc").unwrap();
+    let err = prog.attach().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+    assert!(err.to_string().contains("auto-attachment"), "{err}");
+}
@@ -1,17 +1,26 @@
 use std::os::fd::AsFd;
 
 use scopeguard::defer;
+use serial_test::serial;
 
 mod test;
 use test::bump_rlimit_mlock;
 use test::get_test_object;
+use test::open_test_object;
 
+use libbpf_rs::ErrorKind;
+use libbpf_rs::Program;
+use libbpf_rs::ProgramAttachType;
+use libbpf_rs::ProgramInput;
 use libbpf_rs::Xdp;
+use libbpf_rs::XdpContext;
 use libbpf_rs::XdpFlags;
+use libbpf_rs::XdpOpts;
 
 const LO_IFINDEX: i32 = 1;
 
 #[test]
+#[serial]
 fn test_sudo_xdp() {
     bump_rlimit_mlock();
 
@@ -59,3 +68,177 @@ fn test_sudo_xdp() {
         .detach(LO_IFINDEX, XdpFlags::UPDATE_IF_NOEXIST)
         .is_ok());
 }
+
+#[test]
+#[serial]
+fn test_sudo_xdp_attach_with_opts() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog("xdp_filter").unwrap();
+    let xdp_prog = Xdp::new(prog.as_fd());
+
+    let opts = XdpOpts {
+        flags: XdpFlags::SKB_MODE | XdpFlags::UPDATE_IF_NOEXIST,
+        ..Default::default()
+    };
+    let () = prog.attach_xdp_with_opts(LO_IFINDEX, opts).unwrap();
+    defer! {
+        xdp_prog.detach(LO_IFINDEX, XdpFlags::SKB_MODE).unwrap();
+    }
+
+    let prog_id = Program::get_id_by_fd(prog.as_fd()).unwrap();
+    let attached_id = xdp_prog.query_id(LO_IFINDEX, XdpFlags::SKB_MODE).unwrap();
+    assert_eq!(prog_id, attached_id);
+}
+
+/// Check that we can attach an XDP program via the generic link creation
+/// API and that it gets detached once the link is closed.
+#[test]
+#[serial]
+fn test_sudo_xdp_attach_link_create() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").unwrap();
+    let prog_id = Program::get_id_by_fd(prog.as_fd()).unwrap();
+
+    let err = prog
+        .attach_link_create(LO_IFINDEX, ProgramAttachType::CgroupInetIngress, 0)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let link = prog
+        .attach_link_create(LO_IFINDEX, ProgramAttachType::Xdp, 0)
+        .unwrap();
+
+    let xdp_prog = Xdp::new(prog.as_fd());
+    let attached_id = xdp_prog.query_id(LO_IFINDEX, XdpFlags::empty()).unwrap();
+    assert_eq!(prog_id, attached_id);
+
+    drop(link);
+    let attached_id = xdp_prog.query_id(LO_IFINDEX, XdpFlags::empty()).unwrap();
+    assert_eq!(attached_id, 0);
+}
+
+/// Check that we can attach an XDP program supporting multi-buffer packets
+/// and that other programs are rejected.
+#[test]
+#[serial]
+fn test_sudo_xdp_attach_frags() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").unwrap();
+    let err = prog.attach_xdp_frags(LO_IFINDEX).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut open_obj = open_test_object("xdp.bpf.o");
+    let open_prog = open_obj.prog_mut("xdp_filter").unwrap();
+    let () = open_prog
+        .set_flags(open_prog.flags() | libbpf_sys::BPF_F_XDP_HAS_FRAGS)
+        .unwrap();
+    let mut obj = open_obj.load().unwrap();
+    let prog = obj.prog_mut("xdp_filter").unwrap();
+    let prog_id = Program::get_id_by_fd(prog.as_fd()).unwrap();
+
+    // The loopback device's MTU exceeds the size of a single buffer.
+    let _link = prog.attach_xdp_frags(LO_IFINDEX).unwrap();
+    let xdp_prog = Xdp::new(prog.as_fd());
+    let attached_id = xdp_prog.query_id(LO_IFINDEX, XdpFlags::empty()).unwrap();
+    assert_eq!(attached_id, prog_id);
+}
+
+/// Check that we can atomically replace the program of an XDP link.
+#[test]
+#[serial]
+fn test_sudo_xdp_link_update_program() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").unwrap();
+    let obj1 = get_test_object("xdp.bpf.o");
+    let prog1 = obj1.prog("xdp_filter").unwrap();
+    let prog1_id = Program::get_id_by_fd(prog1.as_fd()).unwrap();
+
+    let mut link = prog.attach_xdp(LO_IFINDEX).unwrap();
+    let () = link.update_program(prog1).unwrap();
+
+    let xdp_prog = Xdp::new(prog1.as_fd());
+    let attached_id = xdp_prog.query_id(LO_IFINDEX, XdpFlags::empty()).unwrap();
+    assert_eq!(attached_id, prog1_id);
+
+    // Programs of a different type are rejected.
+    let tp_obj = get_test_object("tracepoint.bpf.o");
+    let tp_prog = tp_obj.prog("handle__tracepoint").unwrap();
+    let err = link.update_program(tp_prog).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+/// Check that we can test run an XDP program with a context.
+#[test]
+fn test_sudo_xdp_test_run_context() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").unwrap();
+
+    let data = [0u8; 64];
+    let ctx = XdpContext::new(LO_IFINDEX as _, 0).to_bytes(data.len() as _);
+    let input = ProgramInput::new().context_in(&ctx).data_in(&data);
+    let output = prog.test_run(input).unwrap();
+    assert_eq!(output.return_value, libbpf_sys::XDP_PASS);
+
+    // The context has to match the input data.
+    let ctx = XdpContext::new(LO_IFINDEX as _, 0).to_bytes(data.len() as u32 + 1);
+    let input = ProgramInput::new().context_in(&ctx).data_in(&data);
+    let _err = prog.test_run(input).unwrap_err();
+}
+
+/// Check that context buffers not matching the program type's context
+/// struct are rejected before reaching the kernel.
+#[test]
+fn test_sudo_xdp_test_run_context_size() {
+    bump_rlimit_mlock();
+
+    let mut obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog_mut("xdp_filter").unwrap();
+    let size = prog.prog_type().context_struct_size().unwrap();
+
+    let data = [0u8; 64];
+    let mut ctx = vec![0u8; size + 8];
+    ctx[size] = 1;
+    let input = ProgramInput::new().context_in(&ctx).data_in(&data);
+    let err = prog.test_run(input).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let mut ctx_out = vec![0u8; size - 1];
+    let input = ProgramInput::new().data_in(&data).context_out(&mut ctx_out);
+    let err = prog.test_run(input).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+/// Check that we can repeatedly test run an XDP program with a reusable
+/// runner.
+#[test]
+fn test_sudo_xdp_test_runner() {
+    bump_rlimit_mlock();
+
+    let obj = get_test_object("xdp.bpf.o");
+    let prog = obj.prog("xdp_filter").unwrap();
+
+    let data = [0u8; 64];
+    let mut data_out = [0xffu8; 64];
+    let input = ProgramInput::new().data_in(&data).data_out(&mut data_out);
+    let mut runner = prog.test_runner(input).unwrap();
+    for _ in 0..16 {
+        let retval = runner.run().unwrap();
+        assert_eq!(retval, libbpf_sys::XDP_PASS);
+    }
+    assert_eq!(data_out, data);
+
+    // Capturing output data requires an allocation per run.
+    let input = ProgramInput::new().data_in(&data).capture_data_out(true);
+    let err = prog.test_runner(input).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}